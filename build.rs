@@ -0,0 +1,19 @@
+use std::{env, fs, path::Path};
+
+/// City database generated by `just gazetteer`
+const GAZETTEER: &str = "data/gazetteer/cities15000.tsv";
+
+fn main() {
+    println!("cargo::rerun-if-changed={GAZETTEER}");
+
+    // Without the generated database the offline city search finds nothing, rather than
+    // the build failing on a fresh checkout
+    let cities = fs::read_to_string(GAZETTEER).unwrap_or_else(|_| {
+        println!("cargo::warning={GAZETTEER} is missing, run `just gazetteer` to bundle it");
+        String::new()
+    });
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out_dir).join("cities15000.tsv"), cities)
+        .expect("the city database can be written to OUT_DIR");
+}
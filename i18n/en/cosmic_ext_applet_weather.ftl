@@ -1,4 +1,91 @@
 latitude = Latitude
 longitude = Longitude
+coordinates-hint = Degrees with N, S, E or W, a "latitude, longitude" pair, a geo: URI or a map link can be pasted too.
+invalid-coordinate = Enter degrees, like 52.52 or 52°31′N.
+latitude-out-of-range = Latitude must be between -90 and 90.
+longitude-out-of-range = Longitude must be between -180 and 180.
 temperature = Temperature unit
 ip-location-toggle = Auto-detect location
+location-source = Detect with
+location-source-geoclue = GeoClue
+location-source-portal = Portal
+location-source-ip = IP address
+location-accuracy = Accuracy
+accuracy-country = Country
+accuracy-city = City
+accuracy-neighborhood = Area
+accuracy-street = Street
+accuracy-exact = Exact
+distance-threshold = Update after
+accuracy-radius = ± { $radius }
+location-unavailable = Location detection is unavailable.
+location-fallback-saved = The saved coordinates are used instead.
+location-fallback-ip = Your IP address is used instead.
+hourly-forecast = Hourly forecast
+daily-forecast = Daily forecast
+today = Today
+pressure = Pressure
+humidity = Humidity
+cloud-cover = Cloud cover
+wind = Wind
+compass-n = N
+compass-ne = NE
+compass-e = E
+compass-se = SE
+compass-s = S
+compass-sw = SW
+compass-w = W
+compass-nw = NW
+precipitation-amount = {$amount} {$unit}
+precipitation-chance = {$amount} {$unit}, {$probability}% chance
+precipitation-probability = {$probability}% chance
+rain-indicator-toggle = Show rain indicator in panel
+units = Units
+units-metric = Metric
+units-imperial = Imperial
+units-uk = UK
+wind-speed = Wind speed
+precipitation = Precipitation
+panel-decimals = Panel temperature decimals
+popup-decimals = Popup temperature decimals
+last-updated = Updated at {$time}
+cached-forecast = Cached forecast from {$time}
+error-offline = No connection to the weather service.
+error-dns = The weather service address could not be resolved. Check your network connection.
+error-http-status = The weather service responded with error { $status }.
+error-rate-limited = The weather service is receiving too many requests.
+error-parse = The weather service sent data that could not be read.
+error-location-lookup = Your location could not be determined from your IP address.
+error-location-denied = Access to your location was denied.
+error-unavailable = The weather service is temporarily unavailable.
+next-retry = Retrying at {$time}
+provider = Weather provider
+data-source = Weather data from {$name} ({$url})
+feels-like = Feels like {$temperature}
+alerts = Weather alerts
+alert-until = Until {$time}
+error-unsupported-location = The selected weather provider has no forecast for this location.
+observation-station = Observed at {$station}
+api-key = API key
+error-missing-api-key = The selected weather provider needs an API key.
+error-invalid-api-key = The weather provider rejected the API key.
+error-quota-exceeded = The API key has used up its allowed number of requests.
+network-settings = Network settings
+contact = Contact address for weather services
+contact-placeholder = name@example.com
+invalid-contact = Enter an email address or a web address.
+endpoint-url = {$name} base URL
+ip-service-url = {$name} URL
+ip-services = IP location services
+self-hosted-ip = Self-hosted
+geocoding-url = Location search URL
+reverse-geocoding-url = Place name URL
+invalid-url = Enter an http or https address without a query string.
+search-location = Search for a city
+offline-search = Look up places offline
+saved-locations = Saved locations
+save-location = Save location
+location-name = Name
+location-number = Location {$number}
+rotate-locations = Rotate panel through locations
+rotation-interval = Show each for
@@ -1,117 +1,1417 @@
-use std::time::Duration;
+use std::{
+    borrow::Cow,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, Utc};
+use cosmic::iced::{Limits, Rectangle, Size, Subscription, event::listen_with, mouse::ScrollDelta};
+
+use crate::{
+    cache::ForecastCache,
+    config::{APP_ID, Flags, SavedLocation, WeatherConfig, flags},
+    coordinates::{self, Axis, CoordinateError},
+    error::FetchError,
+    fl, gazetteer,
+    geocoding::{self, Place},
+    http::{is_valid_base_url, is_valid_contact},
+    location::{
+        self, AccuracyLevel, LocationSource, Locator,
+        ip::{self, IpService},
+    },
+    provider::{Endpoint, ProviderKind, ProviderSettings, WeatherProvider},
+    retry::RetryPolicy,
+    units::{
+        Precipitation, PrecipitationUnit, Pressure, PressureUnit, Temperature, TemperatureUnit,
+        UnitPreset, Units, WindSpeed, WindSpeedUnit,
+    },
+    weather::{AlertSeverity, Conditions, Forecast},
+};
+
+/// Number of hours shown in the popup's hourly forecast strip
+const HOURLY_FORECAST_HOURS: usize = 48;
+
+/// Number of days shown in the popup's daily forecast
+const DAILY_FORECAST_DAYS: usize = 9;
+
+/// Refresh interval used until the provider tells us when its forecast expires
+const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Shortest refresh interval, even if the forecast has already expired
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Selectable numbers of decimal places for temperatures
+const TEMPERATURE_DECIMALS: [u8; 3] = [0, 1, 2];
+
+/// Selectable seconds each location is shown in the panel while rotating
+const ROTATION_INTERVALS: [u64; 4] = [5, 10, 30, 60];
+
+/// Selectable meters the device has to move before its location is updated
+const DISTANCE_THRESHOLDS: [u32; 4] = [100, 1000, 5000, 20000];
+
+/// Pause between naming coordinates as they are typed, which keeps within Nominatim's
+/// limit of one request per second
+const REVERSE_GEOCODING_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pause in typing after which edits in the popup are applied, rather than making a
+/// request or saving the config on every keystroke
+const TYPING_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest location label shown in the panel
+const PANEL_LABEL_LENGTH: usize = 12;
+
+/// Pixels a touchpad has to scroll over the panel button to step to another location
+const SCROLL_STEP_PIXELS: f32 = 40.0;
+
+/// Tallest the popup grows before its content scrolls
+const POPUP_MAX_HEIGHT: f32 = 800.0;
+
+pub fn run() -> cosmic::iced::Result {
+    cosmic::applet::run::<Weather>(flags())
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WeatherUpdate {
+    /// Provider the forecast was requested from
+    pub provider: ProviderKind,
+    /// `None` when the forecast has not changed since the previous update
+    pub forecast: Option<Forecast>,
+    pub expires: Option<DateTime<Utc>>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub city: Option<String>,
+    pub region: Option<String>,
+    /// Radius of the area an IP lookup located the device in, in meters
+    pub accuracy: Option<f64>,
+}
+
+impl WeatherUpdate {
+    /// Coordinates the forecast was requested for, `None` when they were looked up by IP
+    /// address, which is the only lookup naming the place
+    fn requested_location(&self) -> Option<(f64, f64)> {
+        self.city
+            .is_none()
+            .then_some((self.latitude, self.longitude))
+    }
+}
+
+struct Weather {
+    core: cosmic::app::Core,
+    popup: Option<cosmic::iced::window::Id>,
+    config: WeatherConfig,
+    config_handler: Option<cosmic::cosmic_config::Config>,
+    provider: Arc<dyn WeatherProvider>,
+    refresh_interval: Duration,
+    fetched_at: Option<DateTime<Utc>>,
+    /// Whether the shown forecast comes from the disk cache or a failed refresh
+    stale: bool,
+    error: Option<FetchError>,
+    retry_policy: RetryPolicy,
+    next_retry: Option<DateTime<Utc>>,
+    forecast: Forecast,
+    /// Coordinates as typed, which are only saved once valid
+    latitude: String,
+    longitude: String,
+    latitude_error: Option<CoordinateError>,
+    longitude_error: Option<CoordinateError>,
+    location_name: String,
+    search_query: String,
+    /// When the search query was last typed, it is searched for once typing pauses
+    search_edited: Option<Instant>,
+    search_results: Vec<Place>,
+    search_error: Option<FetchError>,
+    offline_search: bool,
+    /// Name looked up for coordinates entered by hand
+    place_name: Option<String>,
+    /// Whether the coordinates entered by hand have changed since they were named
+    reverse_pending: bool,
+    /// Latest forecast of each saved location, in the same order, except the active one
+    /// which uses `forecast`
+    saved_forecasts: Vec<Option<Forecast>>,
+    /// Saved location shown in the panel instead of the active forecast
+    shown_location: Option<usize>,
+    /// Saved location the open popup shows without making it the active one
+    viewed_location: Option<usize>,
+    rotate_locations: bool,
+    rotation_interval: u64,
+    /// Scrolling over the panel button not yet turned into a step, in lines
+    scroll_offset: f32,
+    city: String,
+    region: String,
+    units: Units,
+    panel_temperature_decimals: u8,
+    popup_temperature_decimals: u8,
+    use_ip_location: bool,
+    location_source: LocationSource,
+    location_accuracy: AccuracyLevel,
+    distance_threshold: u32,
+    /// Latest location reported by GeoClue or the portal
+    located: Option<location::Location>,
+    /// Why the location source failed, a fallback is used instead until the source is
+    /// changed
+    locator_error: Option<FetchError>,
+    /// Radius of the area the last IP lookup located the device in, in meters
+    ip_accuracy: Option<f64>,
+    show_rain_indicator: bool,
+    /// Whether the API key is shown in plain text in the popup
+    show_api_key: bool,
+    show_network_settings: bool,
+    /// API key, endpoint URLs and contact as typed, which are only saved once valid
+    settings_input: ProviderSettings,
+    /// When the settings in the popup were last edited, they are saved once typing pauses
+    settings_edited: Option<Instant>,
+    /// Names of the saved locations as typed, saved with the settings once typing pauses
+    location_names: Vec<String>,
+    size: Size,
+}
+
+impl Weather {
+    fn update_weather_data(&mut self) -> cosmic::app::Task<Message> {
+        if !self.use_ip_location {
+            return self.fetch_forecast(self.config.latitude, self.config.longitude);
+        }
+
+        if self.location_source != LocationSource::IpAddress && self.locator_error.is_none() {
+            // The forecast is fetched as soon as the location is reported
+            return self.located.map_or_else(cosmic::Task::none, |location| {
+                self.fetch_forecast(location.latitude, location.longitude)
+            });
+        }
+
+        if self.uses_saved_coordinates() {
+            return self.fetch_forecast(self.config.latitude, self.config.longitude);
+        }
+
+        let provider = self.provider.clone();
+        let kind = provider.kind();
+        let services = self.config.ip_services.clone();
+        let settings = self.config.provider_settings.clone();
+
+        cosmic::Task::perform(
+            async move {
+                let ip = ip::locate(&services, &settings)
+                    .await
+                    .inspect_err(|e| tracing::error!("IP Location API Error: {e}"))?;
+                let response = provider
+                    .forecast(ip.latitude, ip.longitude)
+                    .await
+                    .inspect_err(|e| tracing::error!("Forecast API Error: {e}"))?;
+
+                Ok(WeatherUpdate {
+                    provider: kind,
+                    forecast: response.forecast,
+                    expires: response.expires,
+                    latitude: ip.latitude,
+                    longitude: ip.longitude,
+                    city: Some(ip.city),
+                    region: Some(ip.region),
+                    accuracy: ip.accuracy,
+                })
+            },
+            move |result: Result<WeatherUpdate, FetchError>| match result {
+                Ok(update) => cosmic::action::Action::App(Message::UpdateApplet(update)),
+                Err(e) => cosmic::action::Action::App(Message::UpdateFailed(kind, None, e)),
+            },
+        )
+    }
+
+    /// Whether the portal failed and the coordinates entered before are used instead of
+    /// the IP address, as the user may not want to share the location at all
+    fn uses_saved_coordinates(&self) -> bool {
+        self.location_source == LocationSource::Portal
+            && self.locator_error.is_some()
+            && (self.config.latitude != 0.0 || self.config.longitude != 0.0)
+    }
+
+    /// Whether a response for the given coordinates, `None` for a location looked up by IP
+    /// address, belongs to the current location, which may have changed while the request
+    /// was in flight
+    fn is_current_location(&self, requested: Option<(f64, f64)>) -> bool {
+        let current = if !self.use_ip_location || self.uses_saved_coordinates() {
+            Some((self.config.latitude, self.config.longitude))
+        } else if self.location_source != LocationSource::IpAddress && self.locator_error.is_none()
+        {
+            self.located
+                .map(|location| (location.latitude, location.longitude))
+        } else {
+            // IP lookups only tell the location along with the forecast
+            return requested.is_none();
+        };
+
+        requested.is_some() && current == requested
+    }
+
+    /// Radius of the area the auto-detected location is in, in meters, when known
+    fn accuracy_radius(&self) -> Option<f64> {
+        if !self.use_ip_location || self.uses_saved_coordinates() {
+            None
+        } else if self.location_source != LocationSource::IpAddress && self.locator_error.is_none()
+        {
+            self.located.map(|location| location.accuracy)
+        } else {
+            self.ip_accuracy
+        }
+    }
+
+    /// Saves the IP geolocation services to try, in order
+    fn set_ip_services(&mut self, services: Vec<IpService>) {
+        if let Some(handler) = &self.config_handler
+            && let Err(e) = self.config.set_ip_services(handler, services.clone())
+        {
+            tracing::error!("{e}");
+        }
+
+        self.config.ip_services = services;
+    }
+
+    fn fetch_forecast(&self, latitude: f64, longitude: f64) -> cosmic::app::Task<Message> {
+        let provider = self.provider.clone();
+        let kind = provider.kind();
+
+        cosmic::Task::perform(
+            provider.forecast(latitude, longitude),
+            move |result| match result {
+                Ok(response) => cosmic::action::Action::App(Message::UpdateApplet(WeatherUpdate {
+                    provider: kind,
+                    forecast: response.forecast,
+                    expires: response.expires,
+                    latitude,
+                    longitude,
+                    ..Default::default()
+                })),
+                Err(e) => {
+                    tracing::error!("Failed to get location forecast: {e}");
+                    cosmic::action::Action::App(Message::UpdateFailed(
+                        kind,
+                        Some((latitude, longitude)),
+                        e,
+                    ))
+                }
+            },
+        )
+    }
+
+    /// Fetches the forecast of every saved location but the active one, for their summaries
+    fn update_saved_locations(&self) -> cosmic::app::Task<Message> {
+        let kind = self.provider.kind();
+
+        cosmic::Task::batch(
+            self.config
+                .locations
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| self.config.active_location != Some(*i))
+                .map(|(_, location)| {
+                    let (latitude, longitude) = (location.latitude, location.longitude);
+
+                    cosmic::Task::perform(
+                        self.provider.forecast(latitude, longitude),
+                        move |result| {
+                            let result =
+                                result.map(|response| response.forecast).inspect_err(|e| {
+                                    tracing::warn!("Failed to get saved location forecast: {e}")
+                                });
+                            cosmic::action::Action::App(Message::UpdateSavedLocation(
+                                kind, latitude, longitude, result,
+                            ))
+                        },
+                    )
+                }),
+        )
+    }
+
+    /// Saves the saved locations and which one of them is active
+    fn set_locations(&mut self, locations: Vec<SavedLocation>, active: Option<usize>) {
+        self.save_locations(locations, active);
+        // The indices of the panel's rotation may have changed
+        self.shown_location = None;
+        self.viewed_location = None;
+    }
+
+    /// Saves the saved locations without reordering them, along with the name of the
+    /// active one as the name of the coordinates
+    fn save_locations(&mut self, locations: Vec<SavedLocation>, active: Option<usize>) {
+        let name = active
+            .and_then(|i| locations.get(i))
+            .map(|location| location.name.clone())
+            .filter(|name| *name != self.location_name);
+
+        if let Some(handler) = &self.config_handler {
+            for result in [
+                self.config.set_locations(handler, locations.clone()),
+                self.config.set_active_location(handler, active),
+            ] {
+                if let Err(e) = result {
+                    tracing::error!("{e}");
+                }
+            }
+
+            if let Some(name) = &name
+                && let Err(e) = self.config.set_location_name(handler, name.clone())
+            {
+                tracing::error!("{e}");
+            }
+        }
+
+        if let Some(name) = name {
+            self.location_name = name;
+        }
+        self.location_names = locations
+            .iter()
+            .map(|location| location.name.clone())
+            .collect();
+        self.config.locations = locations;
+        self.config.active_location = active;
+    }
+
+    /// Saved locations with the names typed into the popup, which may not be saved yet
+    fn typed_locations(&self) -> Vec<SavedLocation> {
+        self.config
+            .locations
+            .iter()
+            .zip(&self.location_names)
+            .map(|(location, name)| SavedLocation {
+                name: name.clone(),
+                ..location.clone()
+            })
+            .collect()
+    }
+
+    /// Saved locations with a one-line summary of their current conditions
+    fn saved_locations(&self) -> cosmic::Element<'_, Message> {
+        let now = Utc::now();
+        let count = self.config.locations.len();
+        let mut column = cosmic::iced::widget::column::with_capacity(count + 1).spacing(8);
+
+        for (i, location) in self.config.locations.iter().enumerate() {
+            let forecast = if self.config.active_location == Some(i) {
+                Some(&self.forecast)
+            } else {
+                self.saved_forecasts.get(i).and_then(Option::as_ref)
+            };
+
+            let mut summary = cosmic::widget::row::with_capacity(2)
+                .spacing(4)
+                .align_y(cosmic::iced::alignment::Vertical::Center);
+            match forecast.and_then(|forecast| forecast.current(now)) {
+                Some(current) => {
+                    summary = summary
+                        .push(
+                            cosmic::widget::icon::from_name(current.icon.clone())
+                                .size(16)
+                                .symbolic(true),
+                        )
+                        .push(cosmic::widget::text::caption(
+                            self.format_popup_temperature(current.temperature),
+                        ));
+                }
+                None => summary = summary.push(cosmic::widget::text::caption("--")),
+            }
+
+            let mut row = cosmic::widget::row::with_capacity(5)
+                .push(
+                    cosmic::widget::button::icon(cosmic::widget::icon::from_name(
+                        if self.config.active_location == Some(i) {
+                            "radio-checked-symbolic"
+                        } else {
+                            "radio-symbolic"
+                        },
+                    ))
+                    .on_press(Message::SelectLocation(i)),
+                )
+                .push(
+                    cosmic::iced::widget::column::with_capacity(2)
+                        .push(
+                            cosmic::widget::text_input(
+                                fl!("location-name"),
+                                self.location_names.get(i).unwrap_or(&location.name),
+                            )
+                            .on_input(move |name| Message::RenameLocation(i, name))
+                            .width(cosmic::iced::Length::Fill),
+                        )
+                        .push(summary)
+                        .spacing(4)
+                        .width(cosmic::iced::Length::Fill),
+                )
+                .spacing(4)
+                .align_y(cosmic::iced::alignment::Vertical::Center);
+
+            let mut up =
+                cosmic::widget::button::icon(cosmic::widget::icon::from_name("go-up-symbolic"));
+            if i > 0 {
+                up = up.on_press(Message::MoveLocation(i, i - 1));
+            }
+            let mut down =
+                cosmic::widget::button::icon(cosmic::widget::icon::from_name("go-down-symbolic"));
+            if i + 1 < count {
+                down = down.on_press(Message::MoveLocation(i, i + 1));
+            }
+
+            row = row.push(up).push(down).push(
+                cosmic::widget::button::icon(cosmic::widget::icon::from_name(
+                    "edit-delete-symbolic",
+                ))
+                .on_press(Message::DeleteLocation(i)),
+            );
+
+            column = column.push(row);
+        }
+
+        // Only coordinates entered or searched by hand can be saved
+        if !self.use_ip_location {
+            column = column.push(
+                cosmic::widget::button::text(fl!("save-location")).on_press(Message::AddLocation),
+            );
+        }
+
+        if count > 0 {
+            column = column.push(
+                cosmic::widget::row::with_capacity(3)
+                    .push(cosmic::widget::text(fl!("rotate-locations")))
+                    .push(cosmic::widget::Space::new().width(cosmic::iced::Length::Fill))
+                    .push(
+                        cosmic::widget::toggler(self.rotate_locations)
+                            .on_toggle(Message::ToggleRotateLocations),
+                    ),
+            );
+
+            if self.rotate_locations {
+                column = column.push(unit_selector(
+                    fl!("rotation-interval"),
+                    &ROTATION_INTERVALS,
+                    self.rotation_interval,
+                    rotation_interval_label,
+                    Message::SetRotationInterval,
+                ));
+            }
+        }
+
+        column.into()
+    }
+
+    /// Saves the settings typed into the popup once typing has paused
+    fn apply_settings_input(&mut self) -> cosmic::app::Task<Message> {
+        if self
+            .settings_edited
+            .is_none_or(|edited| edited.elapsed() < TYPING_DELAY)
+        {
+            return cosmic::Task::none();
+        }
+        self.settings_edited = None;
+
+        // Renaming keeps the indices of the panel's rotation
+        let locations = self.typed_locations();
+        if locations != self.config.locations {
+            self.save_locations(locations, self.config.active_location);
+        }
+
+        let mut settings = ProviderSettings {
+            open_weather_map_api_key: self.settings_input.open_weather_map_api_key.clone(),
+            ..self.config.provider_settings.clone()
+        };
+
+        // Keep invalid input in the popup only, the error is shown next to it
+        if is_valid_contact(&self.settings_input.contact) {
+            settings.contact = self.settings_input.contact.trim().to_string();
+        }
+        for endpoint in Endpoint::all() {
+            let url = endpoint.url(&self.settings_input);
+            if is_valid_base_url(url) {
+                endpoint.set_url(&mut settings, url.trim().to_string());
+            }
+        }
+
+        if settings == self.config.provider_settings {
+            return cosmic::Task::none();
+        }
+
+        self.update_provider_settings(settings)
+    }
+
+    /// Saves changed provider settings and refreshes with a provider using them
+    fn update_provider_settings(
+        &mut self,
+        settings: ProviderSettings,
+    ) -> cosmic::app::Task<Message> {
+        if let Some(handler) = &self.config_handler
+            && let Err(e) = self.config.set_provider_settings(handler, settings.clone())
+        {
+            tracing::error!("{e}");
+        }
+
+        self.config.provider_settings = settings;
+        self.provider = self.provider.kind().create(&self.config.provider_settings);
+
+        self.update_weather_data()
+    }
+
+    fn network_settings(&self) -> cosmic::Element<'_, Message> {
+        let defaults = ProviderSettings::default();
+        let mut column = cosmic::iced::widget::column::with_capacity(7).spacing(12);
+
+        let contact = &self.settings_input.contact;
+        column = column.push(settings_field(
+            fl!("contact"),
+            &fl!("contact-placeholder"),
+            contact,
+            (!is_valid_contact(contact)).then(|| fl!("invalid-contact")),
+            Message::UpdateContact,
+        ));
+
+        for endpoint in Endpoint::all() {
+            let label = match endpoint {
+                Endpoint::Provider(kind) => fl!("endpoint-url", name = kind.name()),
+                Endpoint::IpService(service) => {
+                    fl!("ip-service-url", name = ip_service_name(service))
+                }
+                Endpoint::Geocoding => fl!("geocoding-url"),
+                Endpoint::ReverseGeocoding => fl!("reverse-geocoding-url"),
+            };
+            let url = endpoint.url(&self.settings_input);
+
+            column = column.push(settings_field(
+                label,
+                endpoint.url(&defaults),
+                url,
+                (!is_valid_base_url(url)).then(|| fl!("invalid-url")),
+                move |value| Message::UpdateEndpoint(endpoint, value),
+            ));
+        }
+
+        column = column.push(cosmic::widget::text::body(fl!("ip-services")));
+
+        // Enabled services in the order they are tried, then the others
+        let enabled = &self.config.ip_services;
+        let positions = enabled
+            .iter()
+            .enumerate()
+            .map(|(i, service)| (*service, Some(i)));
+        let disabled = IpService::ALL
+            .into_iter()
+            .filter(|service| !enabled.contains(service))
+            .map(|service| (service, None));
+
+        for (service, position) in positions.chain(disabled) {
+            let mut row = cosmic::widget::row::with_capacity(4)
+                .push(
+                    cosmic::widget::text::body(ip_service_name(service))
+                        .width(cosmic::iced::Length::Fill),
+                )
+                .spacing(4)
+                .align_y(cosmic::iced::alignment::Vertical::Center);
+
+            if let Some(i) = position {
+                let mut up =
+                    cosmic::widget::button::icon(cosmic::widget::icon::from_name("go-up-symbolic"));
+                if i > 0 {
+                    up = up.on_press(Message::MoveIpService(i, i - 1));
+                }
+                let mut down = cosmic::widget::button::icon(cosmic::widget::icon::from_name(
+                    "go-down-symbolic",
+                ));
+                if i + 1 < enabled.len() {
+                    down = down.on_press(Message::MoveIpService(i, i + 1));
+                }
+                row = row.push(up).push(down);
+            }
+
+            column = column.push(
+                row.push(
+                    cosmic::widget::toggler(position.is_some())
+                        .on_toggle(move |value| Message::ToggleIpService(service, value)),
+                ),
+            );
+        }
+
+        column.into()
+    }
+
+    /// Forecast shown in the popup, `None` while a viewed saved location has none yet
+    fn popup_forecast(&self) -> Option<&Forecast> {
+        match self.viewed_location {
+            Some(i) => self.saved_forecasts.get(i)?.as_ref(),
+            None => Some(&self.forecast),
+        }
+    }
+
+    /// Conditions shown in the popup header, following the forecast as time passes
+    fn current(&self) -> Option<&Conditions> {
+        self.popup_forecast()?.current(Utc::now())
+    }
+
+    fn icon(&self) -> String {
+        self.current().map_or_else(
+            || String::from("weather-clear"),
+            |current| current.icon.clone(),
+        )
+    }
+
+    fn save_cache(&self, fetched_at: DateTime<Utc>, latitude: f64, longitude: f64) {
+        ForecastCache {
+            fetched_at,
+            provider: self.provider.kind(),
+            latitude,
+            longitude,
+            city: self.city.clone(),
+            region: self.region.clone(),
+            forecast: self.forecast.clone(),
+        }
+        .save();
+    }
+
+    /// Forecast of the location shown in the panel
+    fn panel_forecast(&self) -> Option<&Forecast> {
+        match self.shown_location {
+            Some(i) => self.saved_forecasts.get(i)?.as_ref(),
+            // Without any forecast yet there is nothing to show
+            None => self.fetched_at.and(Some(&self.forecast)),
+        }
+    }
+
+    fn panel_current(&self) -> Option<&Conditions> {
+        self.panel_forecast()?.current(Utc::now())
+    }
+
+    fn format_panel_temperature(&self) -> String {
+        self.panel_current().map_or_else(
+            || String::from("--"),
+            |current| {
+                Temperature(current.temperature)
+                    .format(self.units.temperature, self.panel_temperature_decimals)
+            },
+        )
+    }
+
+    /// Locations the panel steps through, `None` for the active forecast followed by
+    /// the other saved locations
+    fn panel_locations(&self) -> Vec<Option<usize>> {
+        std::iter::once(None)
+            .chain(
+                (0..self.config.locations.len())
+                    .filter(|i| self.config.active_location != Some(*i))
+                    .map(Some),
+            )
+            .collect()
+    }
+
+    fn step_panel_location(&mut self, step: isize) {
+        let locations = self.panel_locations();
+        let position = locations
+            .iter()
+            .position(|location| *location == self.shown_location)
+            .unwrap_or_default();
+
+        self.shown_location =
+            locations[(position as isize + step).rem_euclid(locations.len() as isize) as usize];
+    }
+
+    /// Name of the location shown in the popup
+    fn popup_location_name(&self) -> Option<String> {
+        match self.viewed_location {
+            Some(i) => Some(self.config.locations.get(i)?.name.clone()),
+            None => self.location_display(),
+        }
+    }
+
+    /// Full name of the location shown in the panel
+    fn panel_location_name(&self) -> Option<String> {
+        match self.shown_location.or(self.config.active_location) {
+            Some(i) => Some(self.config.locations.get(i)?.name.clone()),
+            None => self.location_display(),
+        }
+    }
+
+    /// Short name of the location shown in the panel, when it can change
+    fn panel_label(&self) -> Option<String> {
+        if !self.rotate_locations && self.shown_location.is_none() {
+            return None;
+        }
+
+        let name = self.panel_location_name()?;
+
+        // Names of searched places include their region and country, the city is enough here
+        let city = name.split(',').next().unwrap_or_default().trim();
+        Some(city.chars().take(PANEL_LABEL_LENGTH).collect())
+    }
+
+    fn format_popup_temperature(&self, temperature: f32) -> String {
+        Temperature(temperature).format(self.units.temperature, self.popup_temperature_decimals)
+    }
+
+    /// Grid of the current conditions the provider reports, two per row
+    fn weather_details(&self) -> Option<cosmic::Element<'_, Message>> {
+        let current = self.current()?;
+
+        let mut details = Vec::with_capacity(4);
+        if let Some(pressure) = current.pressure {
+            details.push((
+                fl!("pressure"),
+                Pressure(pressure).format(self.units.pressure),
+            ));
+        }
+        if let Some(humidity) = current.humidity {
+            details.push((fl!("humidity"), format!("{humidity:.0}%")));
+        }
+        if let Some(cloud_cover) = current.cloud_cover {
+            details.push((fl!("cloud-cover"), format!("{cloud_cover:.0}%")));
+        }
+        if let Some(wind_speed) = current.wind_speed {
+            let mut wind = WindSpeed(wind_speed).format(self.units.wind_speed);
+            if let Some(wind_direction) = current.wind_direction {
+                wind = format!("{wind} {}", compass_direction(wind_direction));
+            }
+            details.push((fl!("wind"), wind));
+        }
+
+        if details.is_empty() {
+            return None;
+        }
+
+        let mut grid =
+            cosmic::iced::widget::column::with_capacity(details.len().div_ceil(2)).spacing(8);
+
+        for pair in details.chunks(2) {
+            let mut row = cosmic::widget::row::with_capacity(2).spacing(8);
+            for (label, value) in pair {
+                row = row.push(
+                    cosmic::iced::widget::column::with_capacity(2)
+                        .push(cosmic::widget::text::caption(label.clone()))
+                        .push(cosmic::widget::text::body(value.clone()))
+                        .spacing(2)
+                        .width(cosmic::iced::Length::Fill),
+                );
+            }
+            // Keep a lone last detail in the left column
+            if pair.len() == 1 {
+                row = row.push(cosmic::widget::Space::new().width(cosmic::iced::Length::Fill));
+            }
+            grid = grid.push(row);
+        }
+
+        Some(grid.into())
+    }
+
+    fn alerts(&self) -> Option<cosmic::Element<'_, Message>> {
+        let alerts: Vec<_> = self.popup_forecast()?.active_alerts(Utc::now()).collect();
+
+        if alerts.is_empty() {
+            return None;
+        }
+
+        let mut column = cosmic::iced::widget::column::with_capacity(alerts.len()).spacing(8);
+
+        for alert in alerts {
+            let icon = match alert.severity {
+                AlertSeverity::Severe | AlertSeverity::Extreme => "dialog-error-symbolic",
+                _ => "dialog-warning-symbolic",
+            };
+
+            let mut text = cosmic::iced::widget::column::with_capacity(3)
+                .push(cosmic::widget::text::heading(alert.title.clone()))
+                .spacing(2);
+
+            if let Some(end) = alert.end {
+                text = text.push(cosmic::widget::text::caption(fl!(
+                    "alert-until",
+                    time = end.with_timezone(&Local).format("%a %H:%M").to_string()
+                )));
+            }
+            if !alert.description.is_empty() {
+                text = text.push(cosmic::widget::text::caption(alert.description.clone()));
+            }
+
+            column = column.push(
+                cosmic::widget::row::with_capacity(2)
+                    .push(
+                        cosmic::widget::icon::from_name(icon)
+                            .size(16)
+                            .symbolic(true),
+                    )
+                    .push(text)
+                    .spacing(8),
+            );
+        }
+
+        Some(column.into())
+    }
+
+    fn hourly_forecast(&self) -> Option<cosmic::Element<'_, Message>> {
+        let mut hours = cosmic::widget::row::with_capacity(HOURLY_FORECAST_HOURS).spacing(16);
+        let mut is_empty = true;
+
+        for entry in self
+            .popup_forecast()?
+            .hourly(Utc::now(), HOURLY_FORECAST_HOURS)
+        {
+            is_empty = false;
+
+            let mut hour = cosmic::iced::widget::column::with_capacity(4)
+                .push(cosmic::widget::text::caption(
+                    entry.time.with_timezone(&Local).format("%H:%M").to_string(),
+                ))
+                .push(
+                    cosmic::widget::icon::from_name(entry.icon.clone())
+                        .size(24)
+                        .symbolic(true),
+                )
+                .push(cosmic::widget::text::body(
+                    self.format_popup_temperature(entry.temperature),
+                ))
+                .spacing(4)
+                .align_x(cosmic::iced::alignment::Horizontal::Center);
+
+            if (entry.precipitation > Some(0.0)
+                || entry.precipitation_max > Some(0.0)
+                || entry.precipitation_probability > Some(0.0))
+                && let Some(precipitation) = format_precipitation(
+                    entry.precipitation.map(|amount| {
+                        precipitation_amount(entry, amount, self.units.precipitation)
+                    }),
+                    entry.precipitation_probability,
+                    self.units.precipitation,
+                )
+            {
+                hour = hour.push(cosmic::widget::text::caption(precipitation));
+            }
+
+            hours = hours.push(hour);
+        }
+
+        if is_empty {
+            return None;
+        }
+
+        Some(
+            cosmic::widget::scrollable::horizontal(hours.padding(cosmic::iced::Padding {
+                bottom: 12.0,
+                ..cosmic::iced::Padding::ZERO
+            }))
+            .width(cosmic::iced::Length::Fill)
+            .into(),
+        )
+    }
+
+    fn daily_forecast(&self) -> Option<cosmic::Element<'_, Message>> {
+        let today = Local::now().date_naive();
+        let days: Vec<_> = self
+            .popup_forecast()?
+            .daily(today, DAILY_FORECAST_DAYS)
+            .collect();
+
+        if days.is_empty() {
+            return None;
+        }
+
+        let mut rows = cosmic::iced::widget::column::with_capacity(days.len()).spacing(8);
+
+        for day in days {
+            let label = if day.date == today {
+                fl!("today")
+            } else {
+                day.date.format("%a %-d").to_string()
+            };
+
+            let mut row = cosmic::widget::row::with_capacity(6)
+                .push(cosmic::widget::text::body(label).width(cosmic::iced::Length::Fixed(64.0)))
+                .push(
+                    cosmic::widget::icon::from_name(day.icon.clone())
+                        .size(24)
+                        .symbolic(true),
+                )
+                .push(cosmic::widget::Space::new().width(cosmic::iced::Length::Fill));
+
+            if (day.precipitation > 0.0 || day.precipitation_probability > Some(0.0))
+                && let Some(precipitation) = format_precipitation(
+                    Some(Precipitation(day.precipitation).format_value(self.units.precipitation)),
+                    day.precipitation_probability,
+                    self.units.precipitation,
+                )
+            {
+                row = row.push(cosmic::widget::text::caption(precipitation));
+            }
+
+            row = row
+                .push(cosmic::widget::text::caption(
+                    WindSpeed(day.max_wind_speed).format(self.units.wind_speed),
+                ))
+                .push(cosmic::widget::text::body(format!(
+                    "{} / {}",
+                    self.format_popup_temperature(day.max_temperature),
+                    self.format_popup_temperature(day.min_temperature),
+                )))
+                .spacing(12)
+                .align_y(cosmic::iced::alignment::Vertical::Center);
+
+            rows = rows.push(row);
+        }
+
+        Some(rows.into())
+    }
+
+    fn rain_expected(&self) -> bool {
+        self.panel_forecast().is_some_and(|forecast| {
+            forecast
+                .hourly(Utc::now(), 1)
+                .next()
+                .is_some_and(|entry| entry.expects_precipitation())
+        })
+    }
+
+    fn location_display(&self) -> Option<String> {
+        if !self.use_ip_location {
+            return if self.location_name.is_empty() {
+                self.place_name.clone()
+            } else {
+                Some(self.location_name.clone())
+            };
+        }
+
+        if self.city.is_empty() && self.region.is_empty() {
+            None
+        } else {
+            Some(format!("{}, {}", self.city, self.region))
+        }
+    }
+
+    /// Fills the coordinate fields with valid coordinates
+    fn show_coordinates(&mut self, latitude: f64, longitude: f64) {
+        self.latitude = format!("{latitude:.4}");
+        self.longitude = format!("{longitude:.4}");
+        self.latitude_error = None;
+        self.longitude_error = None;
+    }
+
+    /// Takes a coordinate as typed, or both when a pair or a map link is pasted into
+    /// either field, and saves them once they are valid
+    fn update_coordinate(&mut self, axis: Axis, value: String) -> cosmic::app::Task<Message> {
+        let parsed = match coordinates::parse_pair(&value) {
+            Some(Ok((latitude, longitude))) => {
+                self.show_coordinates(latitude, longitude);
+                return self.set_coordinates(latitude, longitude);
+            }
+            Some(Err(e)) => Err(e),
+            None => coordinates::parse(&value, axis),
+        };
+
+        let (input, error) = match axis {
+            Axis::Latitude => (&mut self.latitude, &mut self.latitude_error),
+            Axis::Longitude => (&mut self.longitude, &mut self.longitude_error),
+        };
+        *input = value;
+        *error = parsed.err();
+
+        // Keep invalid input in the popup only, the error is shown below it
+        match (axis, parsed) {
+            (_, Err(_)) => cosmic::Task::none(),
+            (Axis::Latitude, Ok(latitude)) => self.set_coordinates(latitude, self.config.longitude),
+            (Axis::Longitude, Ok(longitude)) => {
+                self.set_coordinates(self.config.latitude, longitude)
+            }
+        }
+    }
+
+    /// Saves coordinates entered by hand and fetches their forecast
+    fn set_coordinates(&mut self, latitude: f64, longitude: f64) -> cosmic::app::Task<Message> {
+        if let Some(handler) = &self.config_handler {
+            for result in [
+                self.config.set_latitude(handler, latitude),
+                self.config.set_longitude(handler, longitude),
+            ] {
+                if let Err(e) = result {
+                    tracing::error!("{e}");
+                }
+            }
+        }
+
+        self.clear_location_name();
+        self.update_weather_data()
+    }
+
+    /// Forgets the searched place name and the active saved location once the
+    /// coordinates are edited by hand
+    fn clear_location_name(&mut self) {
+        self.location_name.clear();
+        self.deactivate_location();
+        self.queue_reverse_geocoding();
+
+        if let Some(handler) = &self.config_handler
+            && let Err(e) = self.config.set_location_name(handler, String::new())
+        {
+            tracing::error!("{e}");
+        }
+    }
+
+    /// Makes a saved location the one shown in the popup and refreshes its forecast
+    fn select_location(&mut self, i: usize) -> cosmic::app::Task<Message> {
+        let Some(location) = self.typed_locations().into_iter().nth(i) else {
+            return cosmic::Task::none();
+        };
+        if self.config.active_location == Some(i) {
+            return cosmic::Task::none();
+        }
+
+        self.deactivate_location();
+        self.set_locations(self.typed_locations(), Some(i));
+
+        // Show the forecast fetched for the summary until the refresh completes
+        if let Some(forecast) = self.saved_forecasts[i].take() {
+            self.forecast = forecast;
+        }
+
+        self.show_coordinates(location.latitude, location.longitude);
+        self.location_name = location.name.clone();
+        self.use_ip_location = false;
+
+        if let Some(handler) = &self.config_handler {
+            for result in [
+                self.config.set_latitude(handler, location.latitude),
+                self.config.set_longitude(handler, location.longitude),
+                self.config.set_location_name(handler, location.name),
+                self.config.set_use_ip_location(handler, false),
+            ] {
+                if let Err(e) = result {
+                    tracing::error!("{e}");
+                }
+            }
+        }
+
+        self.update_weather_data()
+    }
+
+    /// Keeps the forecast of the active saved location for its summary, as it is no
+    /// longer shown in the popup
+    fn deactivate_location(&mut self) {
+        if let Some(i) = self.config.active_location {
+            if let Some(saved) = self.saved_forecasts.get_mut(i) {
+                *saved = Some(self.forecast.clone());
+            }
+            self.set_locations(self.typed_locations(), None);
+        }
+    }
+
+    /// Names the coordinates entered by hand with the next tick of the reverse geocoding
+    /// subscription, unless they are named already
+    fn queue_reverse_geocoding(&mut self) {
+        self.place_name = None;
+        self.reverse_pending = !self.use_ip_location && self.location_name.is_empty();
+    }
+
+    fn reverse_geocode(&mut self) -> cosmic::app::Task<Message> {
+        self.reverse_pending = false;
+        let (latitude, longitude) = (self.config.latitude, self.config.longitude);
+        let on_result = move |result| {
+            cosmic::action::Action::App(Message::ReverseGeocoded(latitude, longitude, result))
+        };
+
+        if self.offline_search {
+            return cosmic::Task::perform(
+                async move { Ok(gazetteer::nearest(latitude, longitude)) },
+                on_result,
+            );
+        }
+
+        let url = self.config.provider_settings.reverse_geocoding_url.clone();
+        let user_agent = self.config.provider_settings.user_agent();
+        let language = current_language();
+
+        cosmic::Task::perform(
+            async move {
+                match geocoding::reverse(&url, &user_agent, latitude, longitude, &language).await {
+                    // Without network access the bundled cities can still name the place
+                    Err(FetchError::Offline | FetchError::Dns) => {
+                        Ok(gazetteer::nearest(latitude, longitude))
+                    }
+                    result => result,
+                }
+            },
+            on_result,
+        )
+    }
+
+    fn search_location(&mut self, query: String) -> cosmic::app::Task<Message> {
+        if self.offline_search {
+            return cosmic::Task::perform(
+                async move {
+                    let places = gazetteer::search(&query);
+                    (query, Ok(places))
+                },
+                |(query, result)| {
+                    cosmic::action::Action::App(Message::SearchResults(query, result))
+                },
+            );
+        }
+
+        let url = self.config.provider_settings.geocoding_url.clone();
+        let user_agent = self.config.provider_settings.user_agent();
+        let language = current_language();
+
+        cosmic::Task::perform(
+            async move {
+                let result = match geocoding::search(&url, &user_agent, &query, &language).await {
+                    // Without network access the bundled cities are still searchable
+                    Err(FetchError::Offline | FetchError::Dns) => Ok(gazetteer::search(&query)),
+                    result => result,
+                };
+                (query, result)
+            },
+            |(query, result)| cosmic::action::Action::App(Message::SearchResults(query, result)),
+        )
+    }
+
+    fn search_results(&self) -> Option<cosmic::Element<'_, Message>> {
+        if let Some(error) = &self.search_error {
+            return Some(cosmic::widget::text::caption(describe_error(error)).into());
+        }
+
+        if self.search_results.is_empty() {
+            return None;
+        }
+
+        let mut column =
+            cosmic::iced::widget::column::with_capacity(self.search_results.len()).spacing(2);
+
+        for place in &self.search_results {
+            column = column.push(
+                cosmic::applet::menu_button(
+                    cosmic::iced::widget::column::with_capacity(2)
+                        .push(cosmic::widget::text::body(place.name.clone()))
+                        .push(cosmic::widget::text::caption(place.details())),
+                )
+                .on_press(Message::SelectPlace(place.clone())),
+            );
+        }
+
+        Some(column.into())
+    }
+}
+
+/// Language of the user interface, which place names are requested in
+fn current_language() -> String {
+    crate::i18n::LANGUAGE_LOADER
+        .current_language()
+        .language
+        .to_string()
+}
+
+/// Explains why typed coordinates are not used
+fn describe_coordinate_error(error: CoordinateError) -> String {
+    match error {
+        CoordinateError::Invalid => fl!("invalid-coordinate"),
+        CoordinateError::OutOfRange(Axis::Latitude) => fl!("latitude-out-of-range"),
+        CoordinateError::OutOfRange(Axis::Longitude) => fl!("longitude-out-of-range"),
+    }
+}
 
-use cosmic::iced::{Rectangle, Size, Subscription, event::listen_with};
+/// Explains a failed refresh to the user
+fn describe_error(error: &FetchError) -> String {
+    match error {
+        FetchError::Offline => fl!("error-offline"),
+        FetchError::Dns => fl!("error-dns"),
+        FetchError::HttpStatus(status) => fl!("error-http-status", status = status.to_string()),
+        FetchError::RateLimited(_) => fl!("error-rate-limited"),
+        FetchError::Unavailable(_) => fl!("error-unavailable"),
+        FetchError::Parse => fl!("error-parse"),
+        FetchError::LocationLookup => fl!("error-location-lookup"),
+        FetchError::LocationDenied => fl!("error-location-denied"),
+        FetchError::UnsupportedLocation => fl!("error-unsupported-location"),
+        FetchError::MissingApiKey => fl!("error-missing-api-key"),
+        FetchError::InvalidApiKey => fl!("error-invalid-api-key"),
+        FetchError::QuotaExceeded => fl!("error-quota-exceeded"),
+    }
+}
 
-use crate::{
-    config::{APP_ID, Flags, WeatherConfig, flags},
-    fl,
-    weather::{IpApi, WeatherApi},
-};
+/// Amount of precipitation, as the range the forecast is uncertain between when known
+fn precipitation_amount(conditions: &Conditions, amount: f32, unit: PrecipitationUnit) -> String {
+    match (conditions.precipitation_min, conditions.precipitation_max) {
+        (Some(min), Some(max)) if min < max => format!(
+            "{}–{}",
+            Precipitation(min).format_value(unit),
+            Precipitation(max).format_value(unit)
+        ),
+        _ => Precipitation(amount).format_value(unit),
+    }
+}
 
-pub fn run() -> cosmic::iced::Result {
-    cosmic::applet::run::<Weather>(flags())
+/// Formats a precipitation amount and its probability, either of which providers may
+/// leave out
+fn format_precipitation(
+    amount: Option<String>,
+    probability: Option<f32>,
+    unit: PrecipitationUnit,
+) -> Option<String> {
+    match (amount, probability) {
+        (Some(amount), Some(probability)) => Some(fl!(
+            "precipitation-chance",
+            amount = amount,
+            unit = unit.symbol(),
+            probability = format!("{probability:.0}")
+        )),
+        (Some(amount), None) => Some(fl!(
+            "precipitation-amount",
+            amount = amount,
+            unit = unit.symbol()
+        )),
+        (None, Some(probability)) => Some(fl!(
+            "precipitation-probability",
+            probability = format!("{probability:.0}")
+        )),
+        (None, None) => None,
+    }
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct WeatherUpdate {
-    pub temp: i32,
-    pub icon: String,
-    pub uv: f32,
-    pub city: Option<String>,
-    pub region: Option<String>,
+fn ip_service_name(service: IpService) -> String {
+    match service {
+        IpService::SelfHosted => fl!("self-hosted-ip"),
+        service => service.name().to_string(),
+    }
 }
 
-struct Weather {
-    core: cosmic::app::Core,
-    popup: Option<cosmic::iced::window::Id>,
-    config: WeatherConfig,
-    config_handler: Option<cosmic::cosmic_config::Config>,
-    temperature: i32,
-    icon: String,
-    uv: f32,
-    latitude: String,
-    longitude: String,
-    city: String,
-    region: String,
-    use_fahrenheit: bool,
-    use_ip_location: bool,
-    size: Size,
+/// Labelled text input with an inline error message below it
+fn settings_field<'a>(
+    label: String,
+    placeholder: &str,
+    value: &str,
+    error: Option<String>,
+    on_input: impl Fn(String) -> Message + 'a,
+) -> cosmic::Element<'a, Message> {
+    let mut field = cosmic::iced::widget::column::with_capacity(3)
+        .push(cosmic::widget::text::body(label))
+        .push(
+            cosmic::widget::text_input(placeholder.to_string(), value.to_string())
+                .on_input(on_input)
+                .width(cosmic::iced::Length::Fill),
+        )
+        .spacing(4);
+
+    if let Some(error) = error {
+        field = field.push(
+            cosmic::widget::text::caption(error).class(cosmic::theme::Text::Color(
+                cosmic::iced::Color::from_rgb(0.9, 0.1, 0.1),
+            )),
+        );
+    }
+
+    field.into()
 }
 
-impl Weather {
-    fn update_weather_data(&mut self) -> cosmic::app::Task<Message> {
-        if self.use_ip_location {
-            cosmic::Task::perform(
-                async {
-                    let ip = IpApi::get_location_from_ip()
-                        .await
-                        .map_err(|e| format!("IP Location API Error: {}", e))?;
-                    let weather =
-                        WeatherApi::get_location_forecast(ip.lat.to_string(), ip.lon.to_string())
-                            .await
-                            .map_err(|e| format!("Forecast API Error: {}", e))?;
-
-                    Ok(WeatherUpdate {
-                        temp: weather.temp,
-                        icon: weather.icon,
-                        uv: weather.uv,
-                        city: Some(ip.city),
-                        region: Some(ip.regionName),
-                    })
-                },
-                |result: Result<WeatherUpdate, String>| match result {
-                    Ok(update) => cosmic::action::Action::App(Message::UpdateApplet(update)),
-                    Err(e) => {
-                        tracing::error!("{}", e);
-                        cosmic::action::Action::App(Message::UpdateApplet(WeatherUpdate {
-                            icon: String::from("weather-clear"),
-                            ..Default::default()
-                        }))
-                    }
-                },
-            )
-        } else {
-            cosmic::Task::perform(
-                WeatherApi::get_location_forecast(
-                    self.config.latitude.to_string(),
-                    self.config.longitude.to_string(),
-                ),
-                |result| match result {
-                    Ok(weather) => {
-                        cosmic::action::Action::App(Message::UpdateApplet(WeatherUpdate {
-                            temp: weather.temp,
-                            icon: weather.icon,
-                            uv: weather.uv,
-                            ..Default::default()
-                        }))
-                    }
-                    Err(e) => {
-                        tracing::error!("Failed to get location forecast: {e:?}");
-                        cosmic::action::Action::App(Message::UpdateApplet(WeatherUpdate {
-                            icon: String::from("weather-clear"),
-                            ..Default::default()
-                        }))
-                    }
-                },
-            )
-        }
+/// Sample value illustrating a number of decimal places
+fn decimals_example(decimals: u8) -> &'static str {
+    match decimals {
+        0 => "0",
+        1 => "0.0",
+        _ => "0.00",
+    }
+}
+
+/// Row of buttons selecting one of `units`, highlighting the `selected` one
+fn unit_selector<'a, U: Copy + PartialEq, S: Into<Cow<'a, str>>>(
+    label: String,
+    units: &[U],
+    selected: U,
+    symbol: impl Fn(U) -> S,
+    on_press: impl Fn(U) -> Message,
+) -> cosmic::Element<'a, Message> {
+    let mut row = cosmic::widget::row::with_capacity(units.len() + 2)
+        .push(cosmic::widget::text(label))
+        .push(cosmic::widget::Space::new().width(cosmic::iced::Length::Fill))
+        .spacing(4)
+        .align_y(cosmic::iced::alignment::Vertical::Center);
+
+    for unit in units {
+        row = row.push(
+            cosmic::widget::button::text(symbol(*unit))
+                .class(if *unit == selected {
+                    cosmic::theme::Button::Suggested
+                } else {
+                    cosmic::theme::Button::Standard
+                })
+                .on_press(on_press(*unit)),
+        );
     }
 
-    fn format_temperature(&self) -> String {
-        if self.use_fahrenheit {
-            let fahrenheit = (self.temperature as f64 * 9.0 / 5.0 + 32.0).round() as i32;
-            format!("{fahrenheit}°F")
-        } else {
-            format!("{}°C", self.temperature)
-        }
+    row.into()
+}
+
+/// Locations reported by GeoClue or the portal, ending after the first error
+fn location_updates(
+    &(source, accuracy, distance_threshold): &(LocationSource, AccuracyLevel, u32),
+) -> impl cosmic::iced::futures::Stream<Item = Message> + use<> {
+    enum State {
+        Starting,
+        Started(Locator),
+        Failed,
     }
 
-    fn location_display(&self) -> Option<String> {
-        if self.city.is_empty() && self.region.is_empty() {
-            None
-        } else {
-            Some(format!("{}, {}", self.city, self.region))
-        }
+    cosmic::iced::futures::stream::unfold(State::Starting, move |state| async move {
+        let (mut locator, result) = match state {
+            State::Starting => match Locator::start(source, accuracy, distance_threshold).await {
+                // Without a first location the fallback is used, rather than waiting forever
+                Ok(mut locator) => {
+                    let result = location::timeout(
+                        location::FIRST_LOCATION_TIMEOUT,
+                        locator.next_location(),
+                    )
+                    .await;
+                    (locator, result)
+                }
+                Err(e) => return Some((Message::LocationUpdated(Err(e)), State::Failed)),
+            },
+            State::Started(mut locator) => {
+                let result = locator.next_location().await;
+                (locator, result)
+            }
+            State::Failed => return None,
+        };
+
+        let state = match result {
+            Ok(_) => State::Started(locator),
+            Err(_) => State::Failed,
+        };
+
+        Some((Message::LocationUpdated(result), state))
+    })
+}
+
+/// Formats a radius given in meters, in kilometers from one kilometer on
+fn format_radius(meters: f64) -> String {
+    if meters < 1000.0 {
+        format!("{meters:.0} m")
+    } else {
+        format!("{:.0} km", meters / 1000.0)
+    }
+}
+
+/// Distance of one of the `DISTANCE_THRESHOLDS`
+fn distance_threshold_label(meters: u32) -> &'static str {
+    match meters {
+        100 => "100 m",
+        1000 => "1 km",
+        5000 => "5 km",
+        _ => "20 km",
+    }
+}
+
+/// Duration of one of the `ROTATION_INTERVALS`
+fn rotation_interval_label(seconds: u64) -> &'static str {
+    match seconds {
+        5 => "5 s",
+        10 => "10 s",
+        30 => "30 s",
+        _ => "1 min",
+    }
+}
+
+/// Abbreviated name of the compass point the wind blows from
+fn compass_direction(degrees: f32) -> String {
+    match ((degrees.rem_euclid(360.0) + 22.5) / 45.0) as u8 % 8 {
+        0 => fl!("compass-n"),
+        1 => fl!("compass-ne"),
+        2 => fl!("compass-e"),
+        3 => fl!("compass-se"),
+        4 => fl!("compass-s"),
+        5 => fl!("compass-sw"),
+        6 => fl!("compass-w"),
+        _ => fl!("compass-nw"),
     }
 }
 
@@ -122,10 +1422,47 @@ pub enum Message {
     ToggleWindow,
     PopupClosed(cosmic::iced::window::Id),
     UpdateApplet(WeatherUpdate),
+    /// Provider and coordinates the forecast was requested for, `None` when they were
+    /// looked up by IP address, and why it failed
+    UpdateFailed(ProviderKind, Option<(f64, f64)>, FetchError),
+    SetProvider(ProviderKind),
+    UpdateApiKey(String),
+    ApplySettings,
+    ToggleApiKeyVisibility,
+    ToggleNetworkSettings(bool),
+    UpdateEndpoint(Endpoint, String),
+    ToggleIpService(IpService, bool),
+    MoveIpService(usize, usize),
+    UpdateContact(String),
     UpdateLatitude(String),
     UpdateLongitude(String),
-    ToggleFahrenheit(bool),
+    SearchLocation(String),
+    StartSearch,
+    SearchResults(String, Result<Vec<Place>, FetchError>),
+    ReverseGeocode,
+    /// Name of the place at the latitude and longitude, if any
+    ReverseGeocoded(f64, f64, Result<Option<Place>, FetchError>),
+    SelectPlace(Place),
+    AddLocation,
+    RenameLocation(usize, String),
+    MoveLocation(usize, usize),
+    DeleteLocation(usize),
+    SelectLocation(usize),
+    UpdateSavedLocation(ProviderKind, f64, f64, Result<Option<Forecast>, FetchError>),
+    ToggleRotateLocations(bool),
+    SetRotationInterval(u64),
+    RotateLocation,
+    ScrollPanel(ScrollDelta),
+    ToggleOfflineSearch(bool),
+    SetUnits(Units),
+    SetPanelTemperatureDecimals(u8),
+    SetPopupTemperatureDecimals(u8),
     ToggleIpLocation(bool),
+    SetLocationSource(LocationSource),
+    SetLocationAccuracy(AccuracyLevel),
+    SetDistanceThreshold(u32),
+    LocationUpdated(Result<location::Location, FetchError>),
+    ToggleRainIndicator(bool),
 }
 
 impl cosmic::Application for Weather {
@@ -140,29 +1477,82 @@ impl cosmic::Application for Weather {
         flags: Self::Flags,
     ) -> (Self, cosmic::app::Task<Self::Message>) {
         let config = flags.config;
+        let cache = ForecastCache::load().filter(|cache| {
+            cache.provider == config.provider
+                && (config.use_ip_location || cache.is_for(config.latitude, config.longitude))
+        });
 
-        (
-            Self {
-                core,
-                popup: None,
-                latitude: format!("{:.4}", config.latitude),
-                longitude: format!("{:.4}", config.longitude),
-                use_fahrenheit: config.use_fahrenheit,
-                use_ip_location: config.use_ip_location,
-                config,
-                config_handler: flags.config_handler,
-                temperature: 0,
-                icon: String::from("weather-clear"),
-                uv: 0.0,
-                city: String::new(),
-                region: String::new(),
-                size: Size {
-                    width: 10.,
-                    height: 10.,
-                },
+        let mut applet = Self {
+            core,
+            popup: None,
+            latitude: format!("{:.4}", config.latitude),
+            longitude: format!("{:.4}", config.longitude),
+            latitude_error: None,
+            longitude_error: None,
+            location_name: config.location_name.clone(),
+            search_query: String::new(),
+            search_edited: None,
+            search_results: Vec::new(),
+            search_error: None,
+            offline_search: config.offline_search,
+            place_name: None,
+            reverse_pending: false,
+            saved_forecasts: vec![None; config.locations.len()],
+            shown_location: None,
+            viewed_location: None,
+            rotate_locations: config.rotate_locations,
+            rotation_interval: config.rotation_interval,
+            scroll_offset: 0.0,
+            units: config.units,
+            panel_temperature_decimals: config.panel_temperature_decimals,
+            popup_temperature_decimals: config.popup_temperature_decimals,
+            use_ip_location: config.use_ip_location,
+            location_source: config.location_source,
+            location_accuracy: config.location_accuracy,
+            distance_threshold: config.distance_threshold,
+            located: None,
+            locator_error: None,
+            ip_accuracy: None,
+            show_rain_indicator: config.show_rain_indicator,
+            show_api_key: false,
+            show_network_settings: false,
+            settings_input: config.provider_settings.clone(),
+            settings_edited: None,
+            location_names: config
+                .locations
+                .iter()
+                .map(|location| location.name.clone())
+                .collect(),
+            provider: config.provider.create(&config.provider_settings),
+            config,
+            config_handler: flags.config_handler,
+            refresh_interval: DEFAULT_REFRESH_INTERVAL,
+            fetched_at: None,
+            stale: false,
+            error: None,
+            retry_policy: RetryPolicy::default(),
+            next_retry: None,
+            forecast: Forecast::default(),
+            city: String::new(),
+            region: String::new(),
+            size: Size {
+                width: 10.,
+                height: 10.,
             },
-            cosmic::task::message(Message::Tick),
-        )
+        };
+
+        // Show the last known forecast until the first refresh completes
+        if let Some(cache) = cache {
+            applet.forecast = cache.forecast;
+            applet.city = cache.city;
+            applet.region = cache.region;
+            applet.fetched_at = Some(cache.fetched_at);
+            applet.stale = true;
+        }
+
+        applet.queue_reverse_geocoding();
+
+        (applet, cosmic::task::message(Message::Tick))
     }
 
     fn core(&self) -> &cosmic::app::Core {
@@ -174,6 +1564,47 @@ impl cosmic::Application for Weather {
     }
 
     fn subscription(&self) -> cosmic::iced::Subscription<Message> {
+        let locator = if self.use_ip_location
+            && self.location_source != LocationSource::IpAddress
+            && self.locator_error.is_none()
+        {
+            Subscription::run_with(
+                (
+                    self.location_source,
+                    self.location_accuracy,
+                    self.distance_threshold,
+                ),
+                location_updates,
+            )
+        } else {
+            Subscription::none()
+        };
+
+        let rotation = if self.rotate_locations && self.panel_locations().len() > 1 {
+            cosmic::iced::time::every(Duration::from_secs(self.rotation_interval))
+                .map(|_| Message::RotateLocation)
+        } else {
+            Subscription::none()
+        };
+
+        let reverse_geocoding = if self.reverse_pending {
+            cosmic::iced::time::every(REVERSE_GEOCODING_INTERVAL).map(|_| Message::ReverseGeocode)
+        } else {
+            Subscription::none()
+        };
+
+        let settings = if self.settings_edited.is_some() {
+            cosmic::iced::time::every(TYPING_DELAY).map(|_| Message::ApplySettings)
+        } else {
+            Subscription::none()
+        };
+
+        let search = if self.search_edited.is_some() {
+            cosmic::iced::time::every(TYPING_DELAY).map(|_| Message::StartSearch)
+        } else {
+            Subscription::none()
+        };
+
         Subscription::batch([
             listen_with(|event, _status, id| {
                 if let cosmic::iced::Event::Window(
@@ -187,7 +1618,12 @@ impl cosmic::Application for Weather {
                     None
                 }
             }),
-            cosmic::iced::time::every(Duration::from_secs(60)).map(|_| Message::Tick),
+            cosmic::iced::time::every(self.refresh_interval).map(|_| Message::Tick),
+            locator,
+            rotation,
+            reverse_geocoding,
+            settings,
+            search,
         ])
     }
 
@@ -195,105 +1631,492 @@ impl cosmic::Application for Weather {
         Some(cosmic::applet::style())
     }
 
-    fn on_close_requested(&self, id: cosmic::iced::window::Id) -> Option<Message> {
-        Some(Message::PopupClosed(id))
-    }
+    fn on_close_requested(&self, id: cosmic::iced::window::Id) -> Option<Message> {
+        Some(Message::PopupClosed(id))
+    }
+
+    fn update(&mut self, message: Message) -> cosmic::app::Task<Self::Message> {
+        match message {
+            Message::Tick => {
+                return cosmic::Task::batch([
+                    self.update_weather_data(),
+                    self.update_saved_locations(),
+                ]);
+            }
+            Message::UpdateApplet(update) => {
+                // Ignore responses still in flight when the provider or location was changed
+                if update.provider != self.provider.kind()
+                    || !self.is_current_location(update.requested_location())
+                {
+                    return cosmic::Task::none();
+                }
+
+                if let Some(forecast) = update.forecast {
+                    self.forecast = forecast;
+                }
+
+                // Fetch again once the provider has a newer forecast rather than polling it
+                self.refresh_interval = update
+                    .expires
+                    .and_then(|expires| (expires - Utc::now()).to_std().ok())
+                    .unwrap_or(DEFAULT_REFRESH_INTERVAL)
+                    .max(MIN_REFRESH_INTERVAL);
+
+                if let Some(city) = update.city {
+                    self.city = city;
+                    // Only IP lookups name the place, along with how far off they may be
+                    self.ip_accuracy = update.accuracy;
+                }
+                if let Some(region) = update.region {
+                    self.region = region;
+                }
+
+                // A not modified response confirms the cached forecast is current as well
+                let fetched_at = Utc::now();
+                self.fetched_at = Some(fetched_at);
+                self.stale = false;
+                self.error = None;
+                self.retry_policy.reset();
+                self.next_retry = None;
+                self.save_cache(fetched_at, update.latitude, update.longitude);
+            }
+            Message::UpdateFailed(provider, requested, error) => {
+                // Ignore failures still in flight when the provider or location was changed
+                if provider != self.provider.kind() || !self.is_current_location(requested) {
+                    return cosmic::Task::none();
+                }
+
+                // Keep the last good forecast, the current conditions follow it as time passes
+                self.stale = true;
+
+                let delay = self.retry_policy.next_delay(error.retry_after());
+                self.refresh_interval = delay;
+                self.next_retry = chrono::Duration::from_std(delay)
+                    .ok()
+                    .map(|delay| Utc::now() + delay);
+                self.error = Some(error);
+            }
+            Message::SetProvider(kind) => {
+                if kind == self.provider.kind() {
+                    return cosmic::Task::none();
+                }
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(e) = self.config.set_provider(handler, kind)
+                {
+                    tracing::error!("{e}");
+                }
+
+                // The previous provider's forecast and errors no longer apply
+                self.provider = kind.create(&self.config.provider_settings);
+                self.forecast = Forecast::default();
+                self.fetched_at = None;
+                self.stale = false;
+                self.error = None;
+                self.retry_policy.reset();
+                self.next_retry = None;
+                self.saved_forecasts = vec![None; self.config.locations.len()];
+
+                return cosmic::Task::batch([
+                    self.update_weather_data(),
+                    self.update_saved_locations(),
+                ]);
+            }
+            Message::UpdateApiKey(value) => {
+                self.settings_input.open_weather_map_api_key = value;
+                self.settings_edited = Some(Instant::now());
+            }
+            Message::ApplySettings => {
+                return self.apply_settings_input();
+            }
+            Message::ToggleApiKeyVisibility => {
+                self.show_api_key = !self.show_api_key;
+            }
+            Message::ToggleNetworkSettings(value) => {
+                self.show_network_settings = value;
+            }
+            Message::UpdateEndpoint(endpoint, value) => {
+                endpoint.set_url(&mut self.settings_input, value);
+                self.settings_edited = Some(Instant::now());
+            }
+            Message::ToggleIpService(service, enabled) => {
+                let mut services = self.config.ip_services.clone();
+                services.retain(|other| *other != service);
+                if enabled {
+                    services.push(service);
+                }
+                self.set_ip_services(services);
+            }
+            Message::MoveIpService(from, to) => {
+                if from.max(to) >= self.config.ip_services.len() {
+                    return cosmic::Task::none();
+                }
+
+                let mut services = self.config.ip_services.clone();
+                services.swap(from, to);
+                self.set_ip_services(services);
+            }
+            Message::UpdateContact(value) => {
+                self.settings_input.contact = value;
+                self.settings_edited = Some(Instant::now());
+            }
+            Message::ToggleWindow => {
+                if let Some(id) = self.popup.take() {
+                    return cosmic::iced::platform_specific::shell::commands::popup::destroy_popup(
+                        id,
+                    );
+                }
+
+                // Open on the location the panel is showing, which stays a saved one
+                self.viewed_location = self.shown_location;
+
+                let new_id = cosmic::iced::window::Id::unique();
+                self.popup.replace(new_id);
+
+                let mut popup_settings = self.core.applet.get_popup_settings(
+                    self.core.main_window_id().unwrap(),
+                    new_id,
+                    None,
+                    None,
+                    None,
+                );
+                popup_settings.positioner.anchor_rect = Rectangle::<i32> {
+                    x: 0,
+                    y: 0,
+                    width: self.size.width as i32,
+                    height: self.size.height as i32,
+                };
+                popup_settings.positioner.size_limits =
+                    Limits::NONE.min_height(1.0).max_height(POPUP_MAX_HEIGHT);
+
+                return cosmic::iced::platform_specific::shell::commands::popup::get_popup(
+                    popup_settings,
+                );
+            }
+            Message::PopupClosed(id) => {
+                if self.popup.as_ref() == Some(&id) {
+                    self.popup = None;
+                }
+            }
+            Message::UpdateLatitude(value) => {
+                return self.update_coordinate(Axis::Latitude, value);
+            }
+            Message::UpdateLongitude(value) => {
+                return self.update_coordinate(Axis::Longitude, value);
+            }
+            Message::SearchLocation(query) => {
+                self.search_query = query;
+                self.search_error = None;
+
+                // A single letter matches too many places to be useful
+                if self.search_query.trim().chars().count() < 2 {
+                    self.search_edited = None;
+                    self.search_results.clear();
+                    return cosmic::Task::none();
+                }
+
+                // The bundled cities are searched right away, online searches once typing
+                // pauses to keep within the geocoding services' rate limits
+                if self.offline_search {
+                    return self.search_location(self.search_query.clone());
+                }
+                self.search_edited = Some(Instant::now());
+            }
+            Message::StartSearch => {
+                if self
+                    .search_edited
+                    .is_none_or(|edited| edited.elapsed() < TYPING_DELAY)
+                {
+                    return cosmic::Task::none();
+                }
+                self.search_edited = None;
+
+                return self.search_location(self.search_query.clone());
+            }
+            Message::SearchResults(query, result) => {
+                // Drop results of queries that have been typed over since, even if typed
+                // back, which are searched for again once typing pauses
+                if query != self.search_query || self.search_edited.is_some() {
+                    return cosmic::Task::none();
+                }
+
+                match result {
+                    Ok(places) => self.search_results = places,
+                    Err(e) => {
+                        tracing::error!("Location search failed: {e}");
+                        self.search_results.clear();
+                        self.search_error = Some(e);
+                    }
+                }
+            }
+            Message::ReverseGeocode => {
+                return self.reverse_geocode();
+            }
+            Message::ReverseGeocoded(latitude, longitude, result) => {
+                // Drop names of coordinates that have been edited since
+                if latitude != self.config.latitude || longitude != self.config.longitude {
+                    return cosmic::Task::none();
+                }
+
+                match result {
+                    Ok(place) => self.place_name = place.map(|place| place.label()),
+                    Err(e) => tracing::warn!("Could not name the location: {e}"),
+                }
+            }
+            Message::SelectPlace(place) => {
+                self.show_coordinates(place.latitude, place.longitude);
+                self.location_name = place.label();
+                self.search_query.clear();
+                self.search_edited = None;
+                self.search_results.clear();
+                self.deactivate_location();
+
+                if let Some(handler) = &self.config_handler {
+                    for result in [
+                        self.config.set_latitude(handler, place.latitude),
+                        self.config.set_longitude(handler, place.longitude),
+                        self.config
+                            .set_location_name(handler, self.location_name.clone()),
+                    ] {
+                        if let Err(e) = result {
+                            tracing::error!("{e}");
+                        }
+                    }
+                }
+
+                return self.update_weather_data();
+            }
+            Message::SetUnits(units) => {
+                self.units = units;
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(e) = self.config.set_units(handler, units)
+                {
+                    tracing::error!("{e}");
+                }
+            }
+            Message::ToggleIpLocation(value) => {
+                self.use_ip_location = value;
+                self.locator_error = None;
+                if value {
+                    self.deactivate_location();
+                }
+                self.queue_reverse_geocoding();
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(e) = self.config.set_use_ip_location(handler, value)
+                {
+                    tracing::error!("{e}");
+                }
+
+                return self.update_weather_data();
+            }
+            Message::SetLocationSource(source) => {
+                self.location_source = source;
+                self.locator_error = None;
+                self.located = None;
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(e) = self.config.set_location_source(handler, source)
+                {
+                    tracing::error!("{e}");
+                }
+
+                return self.update_weather_data();
+            }
+            Message::SetLocationAccuracy(level) => {
+                // The subscription restarts the location source with the new accuracy
+                self.location_accuracy = level;
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(e) = self.config.set_location_accuracy(handler, level)
+                {
+                    tracing::error!("{e}");
+                }
+            }
+            Message::SetDistanceThreshold(meters) => {
+                self.distance_threshold = meters;
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(e) = self.config.set_distance_threshold(handler, meters)
+                {
+                    tracing::error!("{e}");
+                }
+            }
+            Message::LocationUpdated(result) => match result {
+                Ok(location) => {
+                    tracing::debug!("Location accurate to {:.0} m", location.accuracy);
+                    self.located = Some(location);
+
+                    // The location is not named, the name from an IP lookup may be wrong
+                    self.city.clear();
+                    self.region.clear();
 
-    fn update(&mut self, message: Message) -> cosmic::app::Task<Self::Message> {
-        match message {
-            Message::Tick => {
-                return self.update_weather_data();
-            }
-            Message::UpdateApplet(update) => {
-                self.icon = update.icon;
-                self.temperature = update.temp;
-                self.uv = update.uv;
+                    return self.update_weather_data();
+                }
+                Err(e) => {
+                    tracing::warn!("Location detection failed, using a fallback: {e}");
+                    self.locator_error = Some(e);
 
-                if let Some(city) = update.city {
-                    self.city = city;
+                    return self.update_weather_data();
                 }
-                if let Some(region) = update.region {
-                    self.region = region;
+            },
+            Message::SetPanelTemperatureDecimals(value) => {
+                self.panel_temperature_decimals = value;
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(e) = self.config.set_panel_temperature_decimals(handler, value)
+                {
+                    tracing::error!("{e}");
                 }
             }
-            Message::ToggleWindow => {
-                if let Some(id) = self.popup.take() {
-                    return cosmic::iced::platform_specific::shell::commands::popup::destroy_popup(
-                        id,
-                    );
+            Message::SetPopupTemperatureDecimals(value) => {
+                self.popup_temperature_decimals = value;
+
+                if let Some(handler) = &self.config_handler
+                    && let Err(e) = self.config.set_popup_temperature_decimals(handler, value)
+                {
+                    tracing::error!("{e}");
                 }
+            }
+            Message::AddLocation => {
+                let name = if self.location_name.is_empty() {
+                    fl!(
+                        "location-number",
+                        number = (self.config.locations.len() + 1).to_string()
+                    )
+                } else {
+                    self.location_name.clone()
+                };
 
-                let new_id = cosmic::iced::window::Id::unique();
-                self.popup.replace(new_id);
+                self.deactivate_location();
 
-                let mut popup_settings = self.core.applet.get_popup_settings(
-                    self.core.main_window_id().unwrap(),
-                    new_id,
-                    None,
-                    None,
-                    None,
-                );
-                popup_settings.positioner.anchor_rect = Rectangle::<i32> {
-                    x: 0,
-                    y: 0,
-                    width: self.size.width as i32,
-                    height: self.size.height as i32,
-                };
+                let mut locations = self.typed_locations();
+                locations.push(SavedLocation {
+                    name,
+                    latitude: self.config.latitude,
+                    longitude: self.config.longitude,
+                });
+                self.saved_forecasts.push(None);
+                let active = Some(locations.len() - 1);
+                self.set_locations(locations, active);
+            }
+            Message::RenameLocation(i, name) => {
+                if let Some(typed) = self.location_names.get_mut(i) {
+                    *typed = name;
+                    self.settings_edited = Some(Instant::now());
+                }
+            }
+            Message::MoveLocation(from, to) => {
+                if from.max(to) >= self.config.locations.len() {
+                    return cosmic::Task::none();
+                }
 
-                return cosmic::iced::platform_specific::shell::commands::popup::get_popup(
-                    popup_settings,
-                );
+                let mut locations = self.typed_locations();
+                locations.swap(from, to);
+                self.saved_forecasts.swap(from, to);
+                let active = self.config.active_location.map(|active| match active {
+                    _ if active == from => to,
+                    _ if active == to => from,
+                    _ => active,
+                });
+                self.set_locations(locations, active);
             }
-            Message::PopupClosed(id) => {
-                if self.popup.as_ref() == Some(&id) {
-                    self.popup = None;
+            Message::DeleteLocation(i) => {
+                if i >= self.config.locations.len() {
+                    return cosmic::Task::none();
                 }
+
+                let mut locations = self.typed_locations();
+                locations.remove(i);
+                self.saved_forecasts.remove(i);
+                // The coordinates stay, they are just no longer a saved location
+                let active = match self.config.active_location {
+                    Some(active) if active == i => None,
+                    Some(active) if active > i => Some(active - 1),
+                    active => active,
+                };
+                self.set_locations(locations, active);
             }
-            Message::UpdateLatitude(value) => {
-                self.latitude = value.clone();
+            Message::SelectLocation(i) => {
+                return self.select_location(i);
+            }
+            Message::ToggleRotateLocations(value) => {
+                self.rotate_locations = value;
+                if !value {
+                    self.shown_location = None;
+                }
 
                 if let Some(handler) = &self.config_handler
-                    && let Err(e) = self
-                        .config
-                        .set_latitude(handler, value.parse::<f64>().unwrap_or_default())
+                    && let Err(e) = self.config.set_rotate_locations(handler, value)
                 {
                     tracing::error!("{e}");
                 }
-
-                return self.update_weather_data();
             }
-            Message::UpdateLongitude(value) => {
-                self.longitude = value.clone();
+            Message::SetRotationInterval(value) => {
+                self.rotation_interval = value;
 
                 if let Some(handler) = &self.config_handler
-                    && let Err(e) = self
-                        .config
-                        .set_longitude(handler, value.parse::<f64>().unwrap_or_default())
+                    && let Err(e) = self.config.set_rotation_interval(handler, value)
                 {
                     tracing::error!("{e}");
                 }
+            }
+            Message::RotateLocation => {
+                self.step_panel_location(1);
+            }
+            Message::ScrollPanel(delta) => {
+                self.scroll_offset += match delta {
+                    ScrollDelta::Lines { y, .. } => y,
+                    ScrollDelta::Pixels { y, .. } => y / SCROLL_STEP_PIXELS,
+                };
 
-                return self.update_weather_data();
+                // Touchpads report many small deltas, only whole steps change the location
+                if self.scroll_offset.abs() >= 1.0 {
+                    // Scrolling down moves on to the next location
+                    self.step_panel_location(if self.scroll_offset < 0.0 { 1 } else { -1 });
+                    self.scroll_offset = 0.0;
+                }
+            }
+            Message::UpdateSavedLocation(kind, latitude, longitude, result) => {
+                // Ignore responses still in flight when the provider was changed
+                if kind != self.provider.kind() {
+                    return cosmic::Task::none();
+                }
+
+                // Keep the previous forecast when it has not changed or could not be fetched
+                if let Ok(Some(forecast)) = result {
+                    for (location, saved) in
+                        self.config.locations.iter().zip(&mut self.saved_forecasts)
+                    {
+                        if location.latitude == latitude && location.longitude == longitude {
+                            *saved = Some(forecast.clone());
+                        }
+                    }
+                }
             }
-            Message::ToggleFahrenheit(value) => {
-                self.use_fahrenheit = value;
+            Message::ToggleOfflineSearch(value) => {
+                self.offline_search = value;
 
                 if let Some(handler) = &self.config_handler
-                    && let Err(e) = self.config.set_use_fahrenheit(handler, value)
+                    && let Err(e) = self.config.set_offline_search(handler, value)
                 {
                     tracing::error!("{e}");
                 }
+
+                if !self.search_query.is_empty() {
+                    self.search_edited = None;
+                    return self.search_location(self.search_query.clone());
+                }
             }
-            Message::ToggleIpLocation(value) => {
-                self.use_ip_location = value;
+            Message::ToggleRainIndicator(value) => {
+                self.show_rain_indicator = value;
 
                 if let Some(handler) = &self.config_handler
-                    && let Err(e) = self.config.set_use_ip_location(handler, value)
+                    && let Err(e) = self.config.set_show_rain_indicator(handler, value)
                 {
                     tracing::error!("{e}");
                 }
-
-                return self.update_weather_data();
             }
             Message::Size(size) => {
                 self.size = size;
@@ -304,33 +2127,83 @@ impl cosmic::Application for Weather {
     }
 
     fn view(&self) -> cosmic::Element<'_, Message> {
-        let temp = self.core.applet.text(self.format_temperature());
-        let icon = cosmic::widget::icon::from_name(self.icon.clone())
-            .size(self.core.applet.suggested_size(true).0)
-            .symbolic(true);
+        let temp = self.core.applet.text(self.format_panel_temperature());
+        let icon = cosmic::widget::icon::from_name(self.panel_current().map_or_else(
+            || String::from("weather-clear"),
+            |current| current.icon.clone(),
+        ))
+        .size(self.core.applet.suggested_size(true).0)
+        .symbolic(true);
+
+        // Small icons next to the temperature
+        let mut indicators = Vec::with_capacity(2);
+        if self.show_rain_indicator && self.rain_expected() {
+            indicators.push("weather-showers-scattered-symbolic");
+        }
+        if self.error.is_some() {
+            indicators.push("dialog-warning-symbolic");
+        } else if self.stale {
+            indicators.push("document-open-recent-symbolic");
+        }
+
+        let indicators = indicators.into_iter().map(|name| {
+            cosmic::widget::icon::from_name(name)
+                .size(self.core.applet.suggested_size(true).0 / 2)
+                .symbolic(true)
+        });
 
         let data = if self.core.applet.is_horizontal() {
-            cosmic::Element::from(
-                cosmic::widget::row::with_capacity(2)
-                    .push(icon)
-                    .push(temp)
-                    .align_y(cosmic::iced::alignment::Vertical::Center)
-                    .spacing(4),
-            )
+            let mut row = cosmic::widget::row::with_capacity(5)
+                .push(icon)
+                .push(temp)
+                .align_y(cosmic::iced::alignment::Vertical::Center)
+                .spacing(4);
+
+            if let Some(label) = self.panel_label() {
+                row = row.push(self.core.applet.text(label));
+            }
+
+            for indicator in indicators {
+                row = row.push(indicator);
+            }
+
+            cosmic::Element::from(row)
         } else {
-            cosmic::Element::from(
-                cosmic::iced::widget::column::with_capacity(2)
-                    .push(icon)
-                    .push(temp)
-                    .align_x(cosmic::iced::alignment::Horizontal::Center)
-                    .spacing(4),
-            )
+            let mut column = cosmic::iced::widget::column::with_capacity(5)
+                .push(icon)
+                .push(temp)
+                .align_x(cosmic::iced::alignment::Horizontal::Center)
+                .spacing(4);
+
+            if let Some(label) = self.panel_label() {
+                column = column.push(self.core.applet.text(label));
+            }
+
+            for indicator in indicators {
+                column = column.push(indicator);
+            }
+
+            cosmic::Element::from(column)
         };
 
         let button = cosmic::widget::button::custom(data)
             .class(cosmic::theme::Button::AppletIcon)
             .on_press_down(Message::ToggleWindow);
 
+        // Scrolling steps through the saved locations
+        let button = cosmic::iced::widget::mouse_area(button).on_scroll(Message::ScrollPanel);
+
+        // The panel label only fits the city, the tooltip names the whole place
+        let button = match self.panel_location_name() {
+            Some(name) => cosmic::widget::tooltip(
+                button,
+                cosmic::widget::text(name),
+                cosmic::widget::tooltip::Position::Bottom,
+            )
+            .into(),
+            None => cosmic::Element::from(button),
+        };
+
         cosmic::widget::autosize::autosize(button, cosmic::widget::Id::unique()).into()
     }
 
@@ -338,35 +2211,57 @@ impl cosmic::Application for Weather {
         let mut data = cosmic::iced::widget::column::with_capacity(10).padding([16, 0]);
 
         // Weather header with icon, temperature, location, and UV index
-        let weather_icon = cosmic::widget::icon::from_name(self.icon.clone())
+        let weather_icon = cosmic::widget::icon::from_name(self.icon())
             .size(48)
             .symbolic(true);
 
-        let mut weather_info = cosmic::iced::widget::column::with_capacity(3)
-            .push(cosmic::widget::text::title3(self.format_temperature()))
+        let current = self.current();
+
+        let mut weather_info = cosmic::iced::widget::column::with_capacity(6)
+            .push(cosmic::widget::text::title3(current.map_or_else(
+                || String::from("--"),
+                |current| self.format_popup_temperature(current.temperature),
+            )))
             .spacing(4);
 
-        if self.use_ip_location
-            && let Some(location) = self.location_display()
+        if let Some(apparent_temperature) = current.and_then(|current| current.apparent_temperature)
         {
-            weather_info = weather_info.push(
-                cosmic::widget::row::with_capacity(2)
-                    .push(
-                        cosmic::widget::icon::from_name("mark-location-symbolic")
-                            .size(14)
-                            .symbolic(true),
-                    )
-                    .push(cosmic::widget::text::body(location))
-                    .spacing(4)
-                    .align_y(cosmic::iced::alignment::Vertical::Center),
-            );
+            weather_info = weather_info.push(cosmic::widget::text::caption(fl!(
+                "feels-like",
+                temperature = self.format_popup_temperature(apparent_temperature)
+            )));
+        }
+
+        if let Some(location) = self.popup_location_name() {
+            let mut row = cosmic::widget::row::with_capacity(3)
+                .push(
+                    cosmic::widget::icon::from_name("mark-location-symbolic")
+                        .size(14)
+                        .symbolic(true),
+                )
+                .push(cosmic::widget::text::body(location))
+                .spacing(4)
+                .align_y(cosmic::iced::alignment::Vertical::Center);
+
+            if let Some(radius) = self.accuracy_radius()
+                && self.viewed_location.is_none()
+            {
+                row = row.push(cosmic::widget::text::caption(fl!(
+                    "accuracy-radius",
+                    radius = format_radius(radius)
+                )));
+            }
+
+            weather_info = weather_info.push(row);
         }
 
-        if self.uv > 0.0 {
+        if let Some(uv) = current.and_then(|current| current.uv_index)
+            && uv > 0.0
+        {
             // Colour-code the UV index: white (low), yellow (moderate), red (high).
-            let uv_colour = if self.uv < 3.0 {
+            let uv_colour = if uv < 3.0 {
                 cosmic::iced::Color::WHITE
-            } else if self.uv < 6.0 {
+            } else if uv < 6.0 {
                 cosmic::iced::Color::from_rgb(1.0, 0.85, 0.0)
             } else {
                 cosmic::iced::Color::from_rgb(0.9, 0.1, 0.1)
@@ -376,23 +2271,185 @@ impl cosmic::Application for Weather {
                 cosmic::widget::row::with_capacity(2)
                     .push(cosmic::widget::text::caption("UV Index: "))
                     .push(
-                        cosmic::widget::text::caption(format!("{:.1}", self.uv))
+                        cosmic::widget::text::caption(format!("{uv:.1}"))
                             .class(cosmic::theme::Text::Color(uv_colour)),
                     ),
             );
         }
 
+        // NWS only forecasts the probability of precipitation, not its amount
+        if let Some(current) = current
+            && (current.precipitation > Some(0.0)
+                || current.precipitation_max > Some(0.0)
+                || current.precipitation_probability > Some(0.0))
+            && let Some(precipitation) = format_precipitation(
+                current
+                    .precipitation
+                    .map(|amount| precipitation_amount(current, amount, self.units.precipitation)),
+                current.precipitation_probability,
+                self.units.precipitation,
+            )
+        {
+            weather_info = weather_info.push(cosmic::widget::text::caption(precipitation));
+        }
+
+        if let Some(station) = self
+            .popup_forecast()
+            .and_then(|forecast| forecast.station.as_ref())
+        {
+            weather_info = weather_info.push(cosmic::widget::text::caption(fl!(
+                "observation-station",
+                station = station.clone()
+            )));
+        }
+
+        // Saved locations' forecasts are only kept for their summaries
+        if let Some(fetched_at) = self.fetched_at
+            && self.viewed_location.is_none()
+        {
+            // A forecast cached on another day needs the date as well
+            let fetched_at = fetched_at.with_timezone(&Local);
+            let format = if fetched_at.date_naive() == Local::now().date_naive() {
+                "%H:%M"
+            } else {
+                "%a %-d %H:%M"
+            };
+            let time = fetched_at.format(format).to_string();
+
+            weather_info = weather_info.push(cosmic::widget::text::caption(if self.stale {
+                fl!("cached-forecast", time = time)
+            } else {
+                fl!("last-updated", time = time)
+            }));
+        }
+
         let header = cosmic::widget::row::with_capacity(2)
             .push(weather_icon)
             .push(weather_info)
             .spacing(12)
             .align_y(cosmic::iced::alignment::Vertical::Center);
 
-        data =
-            data.push(cosmic::applet::padded_control(header))
+        if let Some(error) = &self.error {
+            data = data.push(cosmic::applet::padded_control(
+                cosmic::widget::row::with_capacity(2)
+                    .push(
+                        cosmic::widget::icon::from_name("dialog-warning-symbolic")
+                            .size(16)
+                            .symbolic(true),
+                    )
+                    .push(cosmic::widget::text::body(describe_error(error)))
+                    .spacing(8)
+                    .align_y(cosmic::iced::alignment::Vertical::Center),
+            ));
+
+            if let Some(next_retry) = self.next_retry {
+                data = data.push(cosmic::applet::padded_control(
+                    cosmic::widget::text::caption(fl!(
+                        "next-retry",
+                        time = next_retry
+                            .with_timezone(&Local)
+                            .format("%H:%M:%S")
+                            .to_string()
+                    )),
+                ));
+            }
+        }
+
+        data = data.push(cosmic::applet::padded_control(header));
+
+        if let Some(details) = self.weather_details() {
+            data = data.push(cosmic::applet::padded_control(details));
+        }
+
+        data = data.push(cosmic::applet::padded_control(
+            cosmic::widget::divider::horizontal::default(),
+        ));
+
+        // Weather warnings for the location
+        if let Some(alerts) = self.alerts() {
+            data = data
+                .push(cosmic::applet::padded_control(
+                    cosmic::iced::widget::column::with_capacity(2)
+                        .push(cosmic::widget::text::heading(fl!("alerts")))
+                        .push(alerts)
+                        .spacing(8),
+                ))
+                .push(cosmic::applet::padded_control(
+                    cosmic::widget::divider::horizontal::default(),
+                ));
+        }
+
+        // Hourly forecast strip
+        if let Some(hourly) = self.hourly_forecast() {
+            data = data
+                .push(cosmic::applet::padded_control(
+                    cosmic::iced::widget::column::with_capacity(2)
+                        .push(cosmic::widget::text::heading(fl!("hourly-forecast")))
+                        .push(hourly)
+                        .spacing(8),
+                ))
+                .push(cosmic::applet::padded_control(
+                    cosmic::widget::divider::horizontal::default(),
+                ));
+        }
+
+        // Daily forecast
+        if let Some(daily) = self.daily_forecast() {
+            data = data
+                .push(cosmic::applet::padded_control(
+                    cosmic::iced::widget::column::with_capacity(2)
+                        .push(cosmic::widget::text::heading(fl!("daily-forecast")))
+                        .push(daily)
+                        .spacing(8),
+                ))
+                .push(cosmic::applet::padded_control(
+                    cosmic::widget::divider::horizontal::default(),
+                ));
+        }
+
+        // Credit for the forecast data, required by most providers
+        if let Some(attribution) = self.popup_forecast().map(|forecast| &forecast.attribution)
+            && !attribution.name.is_empty()
+        {
+            data = data
+                .push(cosmic::applet::padded_control(
+                    cosmic::widget::text::caption(fl!(
+                        "data-source",
+                        name = attribution.name.clone(),
+                        url = attribution.url.clone()
+                    )),
+                ))
                 .push(cosmic::applet::padded_control(
                     cosmic::widget::divider::horizontal::default(),
                 ));
+        }
+
+        // Weather provider selector
+        data = data.push(cosmic::applet::padded_control(unit_selector(
+            fl!("provider"),
+            &ProviderKind::ALL,
+            self.provider.kind(),
+            ProviderKind::name,
+            Message::SetProvider,
+        )));
+
+        if self.provider.kind() == ProviderKind::OpenWeatherMap {
+            data = data.push(cosmic::applet::padded_control(
+                cosmic::iced::widget::column::with_capacity(2)
+                    .push(cosmic::widget::text::body(fl!("api-key")))
+                    .push(
+                        cosmic::widget::secure_input(
+                            fl!("api-key"),
+                            &self.settings_input.open_weather_map_api_key,
+                            Some(Message::ToggleApiKeyVisibility),
+                            !self.show_api_key,
+                        )
+                        .on_input(Message::UpdateApiKey)
+                        .width(cosmic::iced::Length::Fill),
+                    )
+                    .spacing(4),
+            ));
+        }
 
         // IP location toggle
         let ip_location_toggler = cosmic::widget::row::with_capacity(3)
@@ -404,72 +2461,256 @@ impl cosmic::Application for Weather {
 
         data = data.push(cosmic::applet::padded_control(ip_location_toggler));
 
+        if self.use_ip_location {
+            data = data.push(cosmic::applet::padded_control(unit_selector(
+                fl!("location-source"),
+                &LocationSource::ALL,
+                self.location_source,
+                |source| match source {
+                    LocationSource::GeoClue => fl!("location-source-geoclue"),
+                    LocationSource::Portal => fl!("location-source-portal"),
+                    LocationSource::IpAddress => fl!("location-source-ip"),
+                },
+                Message::SetLocationSource,
+            )));
+
+            if let Some(error) = &self.locator_error {
+                let reason = match error {
+                    FetchError::LocationDenied => describe_error(error),
+                    _ => fl!("location-unavailable"),
+                };
+                let fallback = if self.uses_saved_coordinates() {
+                    fl!("location-fallback-saved")
+                } else {
+                    fl!("location-fallback-ip")
+                };
+
+                data = data.push(cosmic::applet::padded_control(
+                    cosmic::widget::text::caption(format!("{reason} {fallback}")),
+                ));
+            } else if self.location_source != LocationSource::IpAddress {
+                data = data.push(cosmic::applet::padded_control(unit_selector(
+                    fl!("location-accuracy"),
+                    &AccuracyLevel::ALL,
+                    self.location_accuracy,
+                    |level| match level {
+                        AccuracyLevel::Country => fl!("accuracy-country"),
+                        AccuracyLevel::City => fl!("accuracy-city"),
+                        AccuracyLevel::Neighborhood => fl!("accuracy-neighborhood"),
+                        AccuracyLevel::Street => fl!("accuracy-street"),
+                        AccuracyLevel::Exact => fl!("accuracy-exact"),
+                    },
+                    Message::SetLocationAccuracy,
+                )));
+                data = data.push(cosmic::applet::padded_control(unit_selector(
+                    fl!("distance-threshold"),
+                    &DISTANCE_THRESHOLDS,
+                    self.distance_threshold,
+                    distance_threshold_label,
+                    Message::SetDistanceThreshold,
+                )));
+            }
+        }
+
         // Manual coordinates input (only when not using IP location)
         if !self.use_ip_location {
             data = data.push(cosmic::applet::padded_control(
                 cosmic::widget::divider::horizontal::default(),
             ));
 
-            let latitude_col = cosmic::iced::widget::column::with_capacity(2)
-                .push(cosmic::widget::text::body(fl!("latitude")))
+            let mut search = cosmic::iced::widget::column::with_capacity(2)
                 .push(
-                    cosmic::widget::text_input(fl!("latitude"), &self.latitude)
-                        .on_input(Message::UpdateLatitude)
+                    cosmic::widget::search_input(fl!("search-location"), &self.search_query)
+                        .on_input(Message::SearchLocation)
+                        .on_clear(Message::SearchLocation(String::new()))
                         .width(cosmic::iced::Length::Fill),
                 )
                 .spacing(4);
 
-            let longitude_col = cosmic::iced::widget::column::with_capacity(2)
-                .push(cosmic::widget::text::body(fl!("longitude")))
+            if let Some(results) = self.search_results() {
+                search = search.push(results);
+            }
+
+            data = data.push(cosmic::applet::padded_control(search));
+
+            let offline_search_toggler = cosmic::widget::row::with_capacity(3)
+                .push(cosmic::widget::text(fl!("offline-search")))
+                .push(cosmic::widget::Space::new().width(cosmic::iced::Length::Fill))
                 .push(
-                    cosmic::widget::text_input(fl!("longitude"), &self.longitude)
-                        .on_input(Message::UpdateLongitude)
-                        .width(cosmic::iced::Length::Fill),
-                )
-                .spacing(4);
+                    cosmic::widget::toggler(self.offline_search)
+                        .on_toggle(Message::ToggleOfflineSearch),
+                );
+
+            data = data.push(cosmic::applet::padded_control(offline_search_toggler));
+
+            let latitude_col = settings_field(
+                fl!("latitude"),
+                &fl!("latitude"),
+                &self.latitude,
+                self.latitude_error.map(describe_coordinate_error),
+                Message::UpdateLatitude,
+            );
+
+            let longitude_col = settings_field(
+                fl!("longitude"),
+                &fl!("longitude"),
+                &self.longitude,
+                self.longitude_error.map(describe_coordinate_error),
+                Message::UpdateLongitude,
+            );
 
             let location_row = cosmic::widget::row::with_capacity(2)
                 .push(latitude_col)
                 .push(longitude_col)
                 .spacing(8);
 
-            data = data.push(cosmic::applet::padded_control(location_row));
+            data = data
+                .push(cosmic::applet::padded_control(location_row))
+                .push(cosmic::applet::padded_control(
+                    cosmic::widget::text::caption(fl!("coordinates-hint")),
+                ));
+        }
+
+        // Saved locations, which can also be picked while using the IP location
+        if !self.use_ip_location || !self.config.locations.is_empty() {
+            data = data
+                .push(cosmic::applet::padded_control(
+                    cosmic::widget::divider::horizontal::default(),
+                ))
+                .push(cosmic::applet::padded_control(
+                    cosmic::widget::text::heading(fl!("saved-locations")),
+                ))
+                .push(cosmic::applet::padded_control(self.saved_locations()));
         }
 
         data = data.push(cosmic::applet::padded_control(
             cosmic::widget::divider::horizontal::default(),
         ));
 
-        // Temperature unit toggle
-        let celsius_btn = cosmic::widget::button::text("°C")
-            .class(if self.use_fahrenheit {
-                cosmic::theme::Button::Standard
-            } else {
-                cosmic::theme::Button::Suggested
-            })
-            .on_press(Message::ToggleFahrenheit(false));
-
-        let fahrenheit_btn = cosmic::widget::button::text("°F")
-            .class(if self.use_fahrenheit {
-                cosmic::theme::Button::Suggested
-            } else {
-                cosmic::theme::Button::Standard
-            })
-            .on_press(Message::ToggleFahrenheit(true));
-
-        let temperature_row = cosmic::widget::row::with_capacity(4)
-            .push(cosmic::widget::text(fl!("temperature")))
+        // Unit presets and per-quantity unit selectors
+        let mut presets = cosmic::widget::row::with_capacity(UnitPreset::ALL.len() + 2)
+            .push(cosmic::widget::text(fl!("units")))
             .push(cosmic::widget::Space::new().width(cosmic::iced::Length::Fill))
-            .push(celsius_btn)
-            .push(fahrenheit_btn)
             .spacing(4)
             .align_y(cosmic::iced::alignment::Vertical::Center);
 
-        data = data.push(cosmic::applet::padded_control(temperature_row));
+        for preset in UnitPreset::ALL {
+            let label = match preset {
+                UnitPreset::Metric => fl!("units-metric"),
+                UnitPreset::Imperial => fl!("units-imperial"),
+                UnitPreset::Uk => fl!("units-uk"),
+            };
+
+            presets = presets.push(
+                cosmic::widget::button::text(label)
+                    .class(if preset.units() == self.units {
+                        cosmic::theme::Button::Suggested
+                    } else {
+                        cosmic::theme::Button::Standard
+                    })
+                    .on_press(Message::SetUnits(preset.units())),
+            );
+        }
+
+        let units = self.units;
+
+        data = data
+            .push(cosmic::applet::padded_control(presets))
+            .push(cosmic::applet::padded_control(unit_selector(
+                fl!("temperature"),
+                &TemperatureUnit::ALL,
+                units.temperature,
+                TemperatureUnit::symbol,
+                |temperature| {
+                    Message::SetUnits(Units {
+                        temperature,
+                        ..units
+                    })
+                },
+            )))
+            .push(cosmic::applet::padded_control(unit_selector(
+                fl!("panel-decimals"),
+                &TEMPERATURE_DECIMALS,
+                self.panel_temperature_decimals,
+                decimals_example,
+                Message::SetPanelTemperatureDecimals,
+            )))
+            .push(cosmic::applet::padded_control(unit_selector(
+                fl!("popup-decimals"),
+                &TEMPERATURE_DECIMALS,
+                self.popup_temperature_decimals,
+                decimals_example,
+                Message::SetPopupTemperatureDecimals,
+            )))
+            .push(cosmic::applet::padded_control(unit_selector(
+                fl!("wind-speed"),
+                &WindSpeedUnit::ALL,
+                units.wind_speed,
+                WindSpeedUnit::symbol,
+                |wind_speed| {
+                    Message::SetUnits(Units {
+                        wind_speed,
+                        ..units
+                    })
+                },
+            )))
+            .push(cosmic::applet::padded_control(unit_selector(
+                fl!("pressure"),
+                &PressureUnit::ALL,
+                units.pressure,
+                PressureUnit::symbol,
+                |pressure| Message::SetUnits(Units { pressure, ..units }),
+            )))
+            .push(cosmic::applet::padded_control(unit_selector(
+                fl!("precipitation"),
+                &PrecipitationUnit::ALL,
+                units.precipitation,
+                PrecipitationUnit::symbol,
+                |precipitation| {
+                    Message::SetUnits(Units {
+                        precipitation,
+                        ..units
+                    })
+                },
+            )))
+            .push(cosmic::applet::padded_control(
+                cosmic::widget::divider::horizontal::default(),
+            ));
+
+        // Endpoint overrides and contact address, hidden unless needed
+        let network_settings_toggler = cosmic::widget::row::with_capacity(3)
+            .push(cosmic::widget::text(fl!("network-settings")))
+            .push(cosmic::widget::Space::new().width(cosmic::iced::Length::Fill))
+            .push(
+                cosmic::widget::toggler(self.show_network_settings)
+                    .on_toggle(Message::ToggleNetworkSettings),
+            );
+
+        data = data.push(cosmic::applet::padded_control(network_settings_toggler));
+
+        if self.show_network_settings {
+            data = data.push(cosmic::applet::padded_control(self.network_settings()));
+        }
+
+        data = data.push(cosmic::applet::padded_control(
+            cosmic::widget::divider::horizontal::default(),
+        ));
+
+        // Panel rain indicator toggle
+        let rain_indicator_toggler = cosmic::widget::row::with_capacity(3)
+            .push(cosmic::widget::text(fl!("rain-indicator-toggle")))
+            .push(cosmic::widget::Space::new().width(cosmic::iced::Length::Fill))
+            .push(
+                cosmic::widget::toggler(self.show_rain_indicator)
+                    .on_toggle(Message::ToggleRainIndicator),
+            );
+
+        data = data.push(cosmic::applet::padded_control(rain_indicator_toggler));
 
+        // The forecast, saved locations and settings are taller than most screens
         self.core
             .applet
-            .popup_container(cosmic::widget::container(data))
+            .popup_container(cosmic::widget::scrollable::vertical(data))
             .into()
     }
 }
@@ -0,0 +1,120 @@
+use std::{fs, io, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::APP_ID, provider::ProviderKind, weather::Forecast};
+
+/// Last successfully fetched forecast, kept on disk so the applet has something
+/// to show on startup and while offline
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForecastCache {
+    pub fetched_at: DateTime<Utc>,
+    #[serde(default)]
+    pub provider: ProviderKind,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub city: String,
+    pub region: String,
+    pub forecast: Forecast,
+}
+
+impl ForecastCache {
+    fn path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join(APP_ID).join("forecast.json"))
+    }
+
+    pub fn load() -> Option<ForecastCache> {
+        let path = Self::path()?;
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                tracing::error!("Failed to read forecast cache {}: {e}", path.display());
+                return None;
+            }
+        };
+
+        serde_json::from_slice(&contents)
+            .map_err(|e| tracing::error!("Failed to parse forecast cache: {e}"))
+            .ok()
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::path() else {
+            return;
+        };
+
+        let result = serde_json::to_vec(self)
+            .map_err(io::Error::from)
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                fs::write(&path, contents)
+            });
+
+        if let Err(e) = result {
+            tracing::error!("Failed to write forecast cache {}: {e}", path.display());
+        }
+    }
+
+    /// Whether the cached forecast was fetched for the given coordinates
+    pub fn is_for(&self, latitude: f64, longitude: f64) -> bool {
+        // Requests are made with four decimals, so anything closer is the same location
+        (self.latitude - latitude).abs() < 1e-4 && (self.longitude - longitude).abs() < 1e-4
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> ForecastCache {
+        ForecastCache {
+            fetched_at: DateTime::from_timestamp(1780315200, 0).unwrap(),
+            provider: ProviderKind::OpenMeteo,
+            latitude: 59.9139,
+            longitude: 10.7522,
+            city: String::from("Oslo"),
+            region: String::from("Oslo County"),
+            forecast: Forecast::default(),
+        }
+    }
+
+    #[test]
+    fn belongs_to_coordinates_within_four_decimals() {
+        let cache = cache();
+
+        assert!(cache.is_for(59.9139, 10.7522));
+        assert!(cache.is_for(59.91393, 10.75217));
+        assert!(!cache.is_for(59.9141, 10.7522));
+        assert!(!cache.is_for(59.9139, 10.7512));
+    }
+
+    #[test]
+    fn reads_back_what_it_wrote() {
+        let contents = serde_json::to_vec(&cache()).unwrap();
+        let cache: ForecastCache = serde_json::from_slice(&contents).unwrap();
+
+        assert_eq!(cache.provider, ProviderKind::OpenMeteo);
+        assert_eq!(cache.city, "Oslo");
+        assert_eq!(cache.fetched_at.timestamp(), 1780315200);
+    }
+
+    #[test]
+    fn reads_caches_from_before_other_providers() {
+        let contents = r#"{
+            "fetched_at": "2026-06-01T12:00:00Z",
+            "latitude": 59.9139,
+            "longitude": 10.7522,
+            "city": "Oslo",
+            "region": "Oslo County",
+            "forecast": {"hourly": [], "daily": []}
+        }"#;
+
+        let cache: ForecastCache = serde_json::from_str(contents).unwrap();
+
+        assert_eq!(cache.provider, ProviderKind::MetNo);
+    }
+}
@@ -1,17 +1,91 @@
 use cosmic::cosmic_config::{
-    self, Config, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry,
+    self, Config, ConfigGet, CosmicConfigEntry, cosmic_config_derive::CosmicConfigEntry,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    location::{AccuracyLevel, LocationSource, ip::IpService},
+    provider::{ProviderKind, ProviderSettings},
+    units::{TemperatureUnit, UnitPreset, Units},
 };
 
 const CONFIG_VERSION: u64 = 1;
 
 pub const APP_ID: &str = "io.github.cosmic_utils.weather-applet";
 
-#[derive(Clone, Default, Debug, CosmicConfigEntry)]
+/// IP location service used before several could be chosen
+const LEGACY_IP_LOCATION_URL: &str = "http://ip-api.com/json";
+
+/// Provider settings of earlier versions which have been renamed since
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct LegacyProviderSettings {
+    ip_location_url: Option<String>,
+}
+
+/// A named place the weather can be switched to from the popup
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedLocation {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(Clone, Debug, CosmicConfigEntry)]
 pub struct WeatherConfig {
+    pub provider: ProviderKind,
+    pub provider_settings: ProviderSettings,
     pub latitude: f64,
     pub longitude: f64,
-    pub use_fahrenheit: bool,
+    /// Name of the place chosen through the location search, empty for coordinates
+    /// entered by hand
+    pub location_name: String,
+    /// Search and name places with the bundled city database instead of the geocoding APIs
+    pub offline_search: bool,
+    pub locations: Vec<SavedLocation>,
+    /// Index of the saved location the coordinates above were taken from
+    pub active_location: Option<usize>,
+    /// Cycle the panel through the saved locations
+    pub rotate_locations: bool,
+    /// Seconds each location is shown in the panel while rotating
+    pub rotation_interval: u64,
+    pub units: Units,
+    pub panel_temperature_decimals: u8,
+    pub popup_temperature_decimals: u8,
     pub use_ip_location: bool,
+    pub location_source: LocationSource,
+    pub location_accuracy: AccuracyLevel,
+    /// Meters the device has to move before GeoClue or the portal report a new location
+    pub distance_threshold: u32,
+    /// IP geolocation services tried in order until one of them answers
+    pub ip_services: Vec<IpService>,
+    pub show_rain_indicator: bool,
+}
+
+impl Default for WeatherConfig {
+    fn default() -> Self {
+        Self {
+            provider: ProviderKind::default(),
+            provider_settings: ProviderSettings::default(),
+            latitude: 0.0,
+            longitude: 0.0,
+            location_name: String::new(),
+            offline_search: false,
+            locations: Vec::new(),
+            active_location: None,
+            rotate_locations: false,
+            rotation_interval: 10,
+            units: UnitPreset::from_locale().units(),
+            panel_temperature_decimals: 0,
+            popup_temperature_decimals: 0,
+            use_ip_location: false,
+            location_source: LocationSource::default(),
+            location_accuracy: AccuracyLevel::default(),
+            distance_threshold: 1000,
+            ip_services: IpService::DEFAULT.to_vec(),
+            show_rain_indicator: false,
+        }
+    }
 }
 
 impl WeatherConfig {
@@ -21,11 +95,53 @@ impl WeatherConfig {
 
     pub fn config() -> WeatherConfig {
         match Self::config_handler() {
-            Some(config_handler) => WeatherConfig::get_entry(&config_handler)
-                .map_err(|error| {
-                    tracing::error!("Error whilst loading config: {:#?}", error);
-                })
-                .unwrap_or_default(),
+            Some(config_handler) => {
+                let mut config = WeatherConfig::get_entry(&config_handler)
+                    .map_err(|error| {
+                        tracing::error!("Error whilst loading config: {:#?}", error);
+                    })
+                    .unwrap_or_default();
+
+                // Carry over the Fahrenheit toggle from before units were configurable, which
+                // overrides the locale's temperature unit either way
+                if config_handler.get::<Units>("units").is_err()
+                    && let Ok(use_fahrenheit) = config_handler.get::<bool>("use_fahrenheit")
+                {
+                    config.units.temperature = if use_fahrenheit {
+                        TemperatureUnit::Fahrenheit
+                    } else {
+                        TemperatureUnit::Celsius
+                    };
+                }
+
+                // Carry over an ip-api.com mirror configured before several IP location services
+                // could be chosen, as the self-hosted one tried first. It is saved right away as
+                // the old key is dropped with the next change of provider settings
+                if config_handler.get::<Vec<IpService>>("ip_services").is_err()
+                    && let Ok(legacy) =
+                        config_handler.get::<LegacyProviderSettings>("provider_settings")
+                    && let Some(url) = legacy.ip_location_url
+                    && url.trim_end_matches('/') != LEGACY_IP_LOCATION_URL
+                {
+                    let settings = ProviderSettings {
+                        self_hosted_ip_url: url,
+                        ..config.provider_settings.clone()
+                    };
+                    let mut services = vec![IpService::SelfHosted];
+                    services.extend(IpService::DEFAULT);
+
+                    for result in [
+                        config.set_provider_settings(&config_handler, settings),
+                        config.set_ip_services(&config_handler, services),
+                    ] {
+                        if let Err(e) = result {
+                            tracing::error!("Error whilst migrating config: {e}");
+                        }
+                    }
+                }
+
+                config
+            }
             None => WeatherConfig::default(),
         }
     }
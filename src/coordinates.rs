@@ -0,0 +1,382 @@
+use std::borrow::Cow;
+
+use reqwest::Url;
+
+/// Which of the two coordinates a value is
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Latitude,
+    Longitude,
+}
+
+impl Axis {
+    /// Largest value in degrees, either side of zero
+    fn max(self) -> f64 {
+        match self {
+            Self::Latitude => 90.0,
+            Self::Longitude => 180.0,
+        }
+    }
+
+    /// Letters of the positive and the negative hemisphere
+    fn hemispheres(self) -> [char; 2] {
+        match self {
+            Self::Latitude => ['N', 'S'],
+            Self::Longitude => ['E', 'W'],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoordinateError {
+    /// Neither decimal degrees nor degrees, minutes and seconds
+    Invalid,
+    /// Beyond 90° for a latitude or 180° for a longitude
+    OutOfRange(Axis),
+}
+
+/// Whether the only comma in `text` separates decimals, like in "52,52"
+fn is_decimal_comma(text: &str) -> bool {
+    text.matches(',').count() == 1
+        && !text.contains('.')
+        && !text.contains(char::is_whitespace)
+        && text
+            .split_once(',')
+            .is_some_and(|(_, decimals)| decimals.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Splits off a hemisphere letter before or after the value, telling whether it is the
+/// negative one
+fn split_hemisphere(text: &str, axis: Axis) -> Result<(&str, bool), CoordinateError> {
+    let [positive, negative] = axis.hemispheres();
+
+    let (letter, rest) = match (text.chars().next(), text.chars().next_back()) {
+        (Some(first), _) if first.is_ascii_alphabetic() => (first, &text[1..]),
+        (_, Some(last)) if last.is_ascii_alphabetic() => (last, &text[..text.len() - 1]),
+        _ => return Ok((text, false)),
+    };
+
+    match letter.to_ascii_uppercase() {
+        letter if letter == positive => Ok((rest, false)),
+        letter if letter == negative => Ok((rest, true)),
+        _ => Err(CoordinateError::Invalid),
+    }
+}
+
+/// Parses a latitude or longitude in decimal degrees, like "-33.8688", or in degrees,
+/// minutes and seconds with an optional hemisphere, like `33°51'54"S`
+pub fn parse(input: &str, axis: Axis) -> Result<f64, CoordinateError> {
+    let text = input.trim();
+    let text = if is_decimal_comma(text) {
+        Cow::Owned(text.replace(',', "."))
+    } else {
+        Cow::Borrowed(text)
+    };
+    let (text, southern_or_western) = split_hemisphere(&text, axis)?;
+
+    let parts: Vec<&str> = text
+        .split(|c: char| c.is_whitespace() || "°º'′’\"″”:".contains(c))
+        .filter(|part| !part.is_empty())
+        .collect();
+    let [degrees, rest @ ..] = parts.as_slice() else {
+        return Err(CoordinateError::Invalid);
+    };
+    if rest.len() > 2 {
+        return Err(CoordinateError::Invalid);
+    }
+
+    let parse_part = |part: &str| part.parse::<f64>().map_err(|_| CoordinateError::Invalid);
+    let signed = degrees.starts_with('-');
+    let mut value = parse_part(degrees)?.abs();
+
+    // Minutes and seconds follow as sixtieths of the part before
+    for (part, divisor) in rest.iter().zip([60.0, 3600.0]) {
+        let part = parse_part(part)?;
+        if !(0.0..60.0).contains(&part) {
+            return Err(CoordinateError::Invalid);
+        }
+        value += part / divisor;
+    }
+
+    // A minus sign together with S or W would cancel out, which is surely a mistake
+    if (signed && southern_or_western) || !value.is_finite() {
+        return Err(CoordinateError::Invalid);
+    }
+    if value > axis.max() {
+        return Err(CoordinateError::OutOfRange(axis));
+    }
+
+    Ok(if signed || southern_or_western {
+        -value
+    } else {
+        value
+    })
+}
+
+fn parse_both(latitude: &str, longitude: &str) -> Result<(f64, f64), CoordinateError> {
+    Ok((
+        parse(latitude, Axis::Latitude)?,
+        parse(longitude, Axis::Longitude)?,
+    ))
+}
+
+/// Splits a pasted pair of coordinates, `None` for a single value
+fn split_pair(text: &str) -> Option<(&str, &str)> {
+    if let Some(pair) = text.split_once(';') {
+        return Some(pair);
+    }
+
+    match text.matches(',').count() {
+        1 if !is_decimal_comma(text) => return text.split_once(','),
+        // Both written with decimal commas, like "52,52, 13,40"
+        3 => return text.split_once(", "),
+        _ => {}
+    }
+
+    // Hemispheres after each value, like 52°31'N 13°24'E, or before, like N52°31' E13°24'
+    if let Some(i) = text.find(['N', 'S', 'n', 's']) {
+        if i == 0 {
+            let j = text.find(['E', 'W', 'e', 'w'])?;
+            return Some(text.split_at(j));
+        }
+
+        let (latitude, longitude) = text.split_at(i + 1);
+        return (!longitude.trim().is_empty()).then_some((latitude, longitude));
+    }
+
+    // Decimal degrees separated by a space, unlike degrees and minutes such as "52 31"
+    match text.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [latitude, longitude]
+            if (latitude.contains('.') || longitude.contains('.'))
+                && latitude.parse::<f64>().is_ok()
+                && longitude.parse::<f64>().is_ok() =>
+        {
+            Some((latitude, longitude))
+        }
+        _ => None,
+    }
+}
+
+/// Parses a "latitude,longitude" pair within a link, ignoring what follows the longitude
+fn parse_link_pair(text: &str) -> Option<Result<(f64, f64), CoordinateError>> {
+    let mut parts = text.split(',');
+    let (latitude, longitude) = (parts.next()?, parts.next()?);
+    let longitude = longitude.split(['(', ' ']).next().unwrap_or_default();
+
+    Some(parse_both(latitude, longitude))
+}
+
+fn query_value(url: &Url, name: &str) -> Option<String> {
+    url.query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Reads a `geo:` URI, like `geo:52.52,13.405;u=35`
+fn parse_geo_uri(url: &Url) -> Result<(f64, f64), CoordinateError> {
+    let coordinates = url.path().split(';').next().unwrap_or_default();
+
+    // Android leaves the coordinates at 0,0 and puts the place in the query instead
+    if coordinates == "0,0"
+        && let Some(Ok(pair)) = query_value(url, "q").as_deref().and_then(parse_link_pair)
+    {
+        return Ok(pair);
+    }
+
+    parse_link_pair(coordinates).unwrap_or(Err(CoordinateError::Invalid))
+}
+
+/// Reads the coordinates out of an OpenStreetMap or Google Maps link
+fn parse_map_link(url: &Url) -> Result<(f64, f64), CoordinateError> {
+    // Google Maps' pin of the place, as !3d<latitude>!4d<longitude> within the data
+    if let Some((_, pin)) = url.as_str().rsplit_once("!3d")
+        && let Some((latitude, rest)) = pin.split_once("!4d")
+    {
+        let longitude = rest.split(['!', '?', '/', '&']).next().unwrap_or_default();
+        return parse_both(latitude, longitude);
+    }
+
+    // OpenStreetMap's marker
+    if let (Some(latitude), Some(longitude)) = (query_value(url, "mlat"), query_value(url, "mlon"))
+    {
+        return parse_both(&latitude, &longitude);
+    }
+
+    // OpenStreetMap's view, as #map=<zoom>/<latitude>/<longitude>
+    if let Some(map) = url.fragment().and_then(|fragment| {
+        fragment
+            .split('&')
+            .find_map(|part| part.strip_prefix("map="))
+    }) && let [_, latitude, longitude, ..] = map.split('/').collect::<Vec<_>>().as_slice()
+    {
+        return parse_both(latitude, longitude);
+    }
+
+    // Google Maps' searches, which may also name an address instead
+    for name in ["q", "query", "ll", "center"] {
+        if let Some(Ok(pair)) = query_value(url, name).as_deref().and_then(parse_link_pair) {
+            return Ok(pair);
+        }
+    }
+
+    // Google Maps' view, as /@<latitude>,<longitude>,<zoom>z
+    url.path_segments()
+        .and_then(|mut segments| segments.find_map(|segment| segment.strip_prefix('@')))
+        .and_then(parse_link_pair)
+        .unwrap_or(Err(CoordinateError::Invalid))
+}
+
+/// Parses both coordinates out of a pasted pair like "-33.8688, 151.2093", a `geo:` URI
+/// or an OpenStreetMap or Google Maps link, `None` when the input is a single value
+pub fn parse_pair(input: &str) -> Option<Result<(f64, f64), CoordinateError>> {
+    let text = input.trim();
+
+    match Url::parse(text) {
+        Ok(url) if url.scheme() == "geo" => return Some(parse_geo_uri(&url)),
+        Ok(url) if matches!(url.scheme(), "http" | "https") => return Some(parse_map_link(&url)),
+        _ => {}
+    }
+
+    let (latitude, longitude) = split_pair(text)?;
+    Some(parse_both(latitude, longitude))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BERLIN: (f64, f64) = (52.52, 13.405);
+
+    #[test]
+    fn parses_decimal_degrees() {
+        assert_eq!(parse("52.52", Axis::Latitude), Ok(52.52));
+        assert_eq!(parse(" -33.8688 ", Axis::Latitude), Ok(-33.8688));
+        assert_eq!(parse("52,52", Axis::Latitude), Ok(52.52));
+        assert_eq!(parse("180", Axis::Longitude), Ok(180.0));
+    }
+
+    #[test]
+    fn parses_degrees_minutes_and_seconds() {
+        let sydney = -(33.0 + 51.0 / 60.0 + 54.0 / 3600.0);
+
+        assert_eq!(parse("33°51'54\"S", Axis::Latitude), Ok(sydney));
+        assert_eq!(parse("S 33° 51' 54\"", Axis::Latitude), Ok(sydney));
+        assert_eq!(parse("33 51 54 s", Axis::Latitude), Ok(sydney));
+        assert_eq!(
+            parse("151°12′33″E", Axis::Longitude),
+            Ok(151.0 + 12.0 / 60.0 + 33.0 / 3600.0)
+        );
+        assert_eq!(
+            parse("13°24.3'W", Axis::Longitude),
+            Ok(-(13.0 + 24.3 / 60.0))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_coordinates() {
+        for input in ["", "abc", "nan", "52.5.1", "52 31 12 4", "52.5E"] {
+            assert_eq!(
+                parse(input, Axis::Latitude),
+                Err(CoordinateError::Invalid),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_a_minus_sign_with_a_southern_or_western_hemisphere() {
+        assert_eq!(
+            parse("-33.86 S", Axis::Latitude),
+            Err(CoordinateError::Invalid)
+        );
+        assert_eq!(
+            parse("W-151.2", Axis::Longitude),
+            Err(CoordinateError::Invalid)
+        );
+        assert_eq!(parse("-33.86 N", Axis::Latitude), Ok(-33.86));
+    }
+
+    #[test]
+    fn rejects_minutes_or_seconds_of_sixty_and_more() {
+        assert_eq!(
+            parse("52 60", Axis::Latitude),
+            Err(CoordinateError::Invalid)
+        );
+        assert_eq!(
+            parse("52 31 75", Axis::Latitude),
+            Err(CoordinateError::Invalid)
+        );
+        assert_eq!(parse("52 59.9", Axis::Latitude), Ok(52.0 + 59.9 / 60.0));
+    }
+
+    #[test]
+    fn rejects_coordinates_out_of_range() {
+        assert_eq!(
+            parse("90.1", Axis::Latitude),
+            Err(CoordinateError::OutOfRange(Axis::Latitude))
+        );
+        assert_eq!(
+            parse("181°W", Axis::Longitude),
+            Err(CoordinateError::OutOfRange(Axis::Longitude))
+        );
+    }
+
+    #[test]
+    fn leaves_single_values_to_parse() {
+        for input in ["52.52", "52,52", "52°31'N", "N52°31'", "52 31 12", "-33.8"] {
+            assert_eq!(parse_pair(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn splits_pasted_pairs() {
+        assert_eq!(parse_pair("52.52, 13.405"), Some(Ok(BERLIN)));
+        assert_eq!(parse_pair("52.52 13.405"), Some(Ok(BERLIN)));
+        assert_eq!(parse_pair("52.52;13.405"), Some(Ok(BERLIN)));
+        assert_eq!(parse_pair("52,52, 13,405"), Some(Ok(BERLIN)));
+        assert_eq!(parse_pair("52,52; 13,405"), Some(Ok(BERLIN)));
+        assert_eq!(parse_pair("52°30'N 13°15'E"), Some(Ok((52.5, 13.25))));
+        assert_eq!(parse_pair("33°30'S, 151°15'E"), Some(Ok((-33.5, 151.25))));
+        assert_eq!(parse_pair("S33°30' W70°30'"), Some(Ok((-33.5, -70.5))));
+        assert_eq!(
+            parse_pair("52.52, 200"),
+            Some(Err(CoordinateError::OutOfRange(Axis::Longitude)))
+        );
+    }
+
+    #[test]
+    fn reads_geo_uris() {
+        assert_eq!(parse_pair("geo:52.52,13.405"), Some(Ok(BERLIN)));
+        assert_eq!(parse_pair("geo:52.52,13.405,34;u=35"), Some(Ok(BERLIN)));
+        assert_eq!(
+            parse_pair("geo:0,0?q=52.52,13.405(Berlin)"),
+            Some(Ok(BERLIN))
+        );
+        assert_eq!(parse_pair("geo:0,0?q=52.52,13.405"), Some(Ok(BERLIN)));
+        // Without coordinates in the query, Android's 0,0 is taken as it is
+        assert_eq!(parse_pair("geo:0,0?q=Berlin"), Some(Ok((0.0, 0.0))));
+        assert_eq!(
+            parse_pair("geo:Berlin"),
+            Some(Err(CoordinateError::Invalid))
+        );
+    }
+
+    #[test]
+    fn reads_map_links() {
+        for link in [
+            "https://www.openstreetmap.org/#map=15/52.5200/13.4050",
+            "https://www.openstreetmap.org/?mlat=52.52&mlon=13.405#map=15/1/2",
+            "https://www.google.com/maps/@52.52,13.405,15z",
+            "https://www.google.com/maps/place/Berlin/@52.50,13.14,10z/data=!3m1!4b1!4m6!3m5!8m2!3d52.52!4d13.405!16z?entry=ttu",
+            "https://maps.google.com/?q=52.52,13.405",
+            "https://www.google.com/maps/search/?api=1&query=52.52%2C13.405",
+        ] {
+            assert_eq!(parse_pair(link), Some(Ok(BERLIN)), "{link}");
+        }
+
+        assert_eq!(
+            parse_pair("https://example.com/"),
+            Some(Err(CoordinateError::Invalid))
+        );
+    }
+}
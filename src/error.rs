@@ -0,0 +1,172 @@
+use std::{error::Error, fmt, time::Duration};
+
+use reqwest::StatusCode;
+
+/// Reasons a weather refresh can fail, kept apart so the popup can explain them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FetchError {
+    /// No connection to the server could be made
+    Offline,
+    /// The server's host name could not be resolved
+    Dns,
+    /// The server answered with an unexpected status code
+    HttpStatus(u16),
+    /// The server refused the request because too many were made, possibly
+    /// saying when to retry
+    RateLimited(Option<Duration>),
+    /// The server is temporarily unavailable, possibly saying when to retry
+    Unavailable(Option<Duration>),
+    /// The response did not have the expected format
+    Parse,
+    /// The location could not be determined from the IP address, by GeoClue or the portal
+    LocationLookup,
+    /// The user did not allow the location portal to share the location
+    LocationDenied,
+    /// The provider has no forecast for the location
+    UnsupportedLocation,
+    /// The provider requires an API key but none is configured
+    MissingApiKey,
+    /// The provider rejected the configured API key
+    InvalidApiKey,
+    /// The API key has used up its allowed number of calls
+    QuotaExceeded,
+}
+
+impl FetchError {
+    /// Delay the server asked for before the next request
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited(retry_after) | Self::Unavailable(retry_after) => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(error: reqwest::Error) -> Self {
+        // The URL may hold an API key in its query string
+        let error = error.without_url();
+        tracing::debug!("Request failed: {error:?}");
+
+        if let Some(status) = error.status() {
+            return match status {
+                StatusCode::TOO_MANY_REQUESTS => Self::RateLimited(None),
+                StatusCode::SERVICE_UNAVAILABLE => Self::Unavailable(None),
+                status => Self::HttpStatus(status.as_u16()),
+            };
+        }
+
+        if error.is_decode() {
+            return Self::Parse;
+        }
+
+        // reqwest does not expose resolver failures, they only show up in the error chain
+        let mut source = error.source();
+        while let Some(inner) = source {
+            if inner.to_string().contains("dns error") {
+                return Self::Dns;
+            }
+            source = inner.source();
+        }
+
+        Self::Offline
+    }
+}
+
+impl From<zbus::Error> for FetchError {
+    fn from(error: zbus::Error) -> Self {
+        tracing::debug!("D-Bus call failed: {error:?}");
+        Self::LocationLookup
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Offline => write!(f, "could not connect to the server"),
+            Self::Dns => write!(f, "could not resolve the server address"),
+            Self::HttpStatus(status) => write!(f, "server responded with status {status}"),
+            Self::RateLimited(_) => write!(f, "too many requests"),
+            Self::Unavailable(_) => write!(f, "service temporarily unavailable"),
+            Self::Parse => write!(f, "could not parse the response"),
+            Self::LocationLookup => write!(f, "could not determine the location"),
+            Self::LocationDenied => write!(f, "access to the location was denied"),
+            Self::UnsupportedLocation => write!(f, "location not covered by the provider"),
+            Self::MissingApiKey => write!(f, "no API key configured"),
+            Self::InvalidApiKey => write!(f, "invalid API key"),
+            Self::QuotaExceeded => write!(f, "API key quota exceeded"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+
+    use super::*;
+    use crate::http::{
+        check_status,
+        mock::{self, MockServer},
+    };
+
+    async fn fetch(url: &str) -> Result<serde_json::Value, FetchError> {
+        let response = check_status(reqwest::get(url).await?)?;
+        Ok(response.json().await?)
+    }
+
+    #[test]
+    fn tells_server_errors_apart() {
+        let cases = [
+            ("404 Not Found", None, "", FetchError::HttpStatus(404)),
+            (
+                "429 Too Many Requests",
+                Some("30"),
+                "",
+                FetchError::RateLimited(Some(Duration::from_secs(30))),
+            ),
+            (
+                "503 Service Unavailable",
+                Some("soon"),
+                "",
+                FetchError::Unavailable(None),
+            ),
+            ("200 OK", None, "<html></html>", FetchError::Parse),
+        ];
+
+        for (status, retry_after, body, expected) in cases {
+            let headers: Vec<_> = retry_after
+                .map(|value| ("Retry-After", value))
+                .into_iter()
+                .collect();
+            let server = MockServer::respond(status, &headers, body);
+
+            let error = mock::block_on(fetch(&server.url)).unwrap_err();
+
+            assert_eq!(error, expected, "{status}");
+        }
+    }
+
+    #[test]
+    fn tells_connection_failures_apart() {
+        let closed = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let error = mock::block_on(fetch(&format!("http://{closed}/"))).unwrap_err();
+        assert_eq!(error, FetchError::Offline);
+
+        // The .invalid top-level domain never resolves
+        let error = mock::block_on(fetch("http://weather.invalid/")).unwrap_err();
+        assert_eq!(error, FetchError::Dns);
+    }
+
+    #[test]
+    fn keeps_the_delay_the_server_asked_for() {
+        let delay = Some(Duration::from_secs(90));
+
+        assert_eq!(FetchError::RateLimited(delay).retry_after(), delay);
+        assert_eq!(FetchError::Unavailable(delay).retry_after(), delay);
+        assert_eq!(FetchError::HttpStatus(500).retry_after(), None);
+    }
+}
@@ -0,0 +1,341 @@
+use std::sync::LazyLock;
+
+use crate::geocoding::{MAX_RESULTS, Place};
+
+/// Cities with more than 15 000 inhabitants from GeoNames, regenerated by `just gazetteer`
+/// and copied by the build script, as tab separated name, region, country, latitude,
+/// longitude and population, one city per line, or nothing when it was not generated
+const CITIES: &str = include_str!(concat!(env!("OUT_DIR"), "/cities15000.tsv"));
+
+/// Shortest query for which a typo is tolerated, shorter ones match too many names
+const MIN_FUZZY_LENGTH: usize = 4;
+
+/// Farthest a city may be from coordinates to name them, in kilometers
+const MAX_NEAREST_DISTANCE: f64 = 50.0;
+
+/// Mean radius of the earth in kilometers
+const EARTH_RADIUS: f64 = 6371.0;
+
+/// Letters with diacritics and the letter they are searched as
+const FOLDS: &[(&str, char)] = &[
+    ("àáâãäåāăąǎ", 'a'),
+    ("çćĉċč", 'c'),
+    ("ďđð", 'd'),
+    ("èéêëēĕėęě", 'e'),
+    ("ĝğġģ", 'g'),
+    ("ĥħ", 'h'),
+    ("ìíîïĩīĭįıǐ", 'i'),
+    ("ĵ", 'j'),
+    ("ķ", 'k'),
+    ("ĺļľŀł", 'l'),
+    ("ñńņňŉ", 'n'),
+    ("òóôõöøōŏőǒ", 'o'),
+    ("ŕŗř", 'r'),
+    ("śŝşšș", 's'),
+    ("ţťŧț", 't'),
+    ("ùúûüũūŭůűųǔ", 'u'),
+    ("ŵ", 'w'),
+    ("ýÿŷ", 'y'),
+    ("źżž", 'z'),
+];
+
+struct Entry {
+    place: Place,
+    /// Folded name the query is compared with
+    key: Vec<char>,
+}
+
+static ENTRIES: LazyLock<Vec<Entry>> =
+    LazyLock::new(|| CITIES.lines().filter_map(parse_line).collect());
+
+fn parse_line(line: &str) -> Option<Entry> {
+    let mut fields = line.split('\t');
+    let name = fields.next()?.to_string();
+    let region = fields.next()?;
+    let country = fields.next()?;
+    let latitude = fields.next()?.parse().ok()?;
+    let longitude = fields.next()?.parse().ok()?;
+    let population = fields.next().and_then(|population| population.parse().ok());
+
+    Some(Entry {
+        key: fold(&name),
+        place: Place {
+            name,
+            region: (!region.is_empty()).then(|| region.to_string()),
+            country: (!country.is_empty()).then(|| country.to_string()),
+            latitude,
+            longitude,
+            population,
+        },
+    })
+}
+
+/// Lowercases and strips diacritics, so that "zurich" finds "Zürich"
+fn fold(text: &str) -> Vec<char> {
+    let mut folded = Vec::with_capacity(text.len());
+
+    for c in text.chars().flat_map(char::to_lowercase) {
+        match c {
+            'ß' => folded.extend(['s', 's']),
+            'æ' => folded.extend(['a', 'e']),
+            'œ' => folded.extend(['o', 'e']),
+            'þ' => folded.extend(['t', 'h']),
+            _ => folded.push(
+                FOLDS
+                    .iter()
+                    .find(|(letters, _)| letters.contains(c))
+                    .map_or(c, |(_, letter)| *letter),
+            ),
+        }
+    }
+
+    folded
+}
+
+/// How a name matches the query, from best to worst
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Match {
+    Exact,
+    Prefix,
+    /// A later word of the name starts with the query, like "york" in "New York"
+    WordPrefix,
+    /// The name starts with the query after fixing a single typo
+    Typo,
+}
+
+fn matches(key: &[char], query: &[char]) -> Option<Match> {
+    if key == query {
+        Some(Match::Exact)
+    } else if key.starts_with(query) {
+        Some(Match::Prefix)
+    } else if (1..key.len())
+        .any(|i| matches!(key[i - 1], ' ' | '-' | '\'') && key[i..].starts_with(query))
+    {
+        Some(Match::WordPrefix)
+    } else if query.len() >= MIN_FUZZY_LENGTH && starts_with_one_edit(key, query) {
+        Some(Match::Typo)
+    } else {
+        None
+    }
+}
+
+/// Whether `key` starts with `query` once one character of the query is replaced,
+/// added, removed or swapped with the next one
+fn starts_with_one_edit(key: &[char], query: &[char]) -> bool {
+    let common = key.iter().zip(query).take_while(|(a, b)| a == b).count();
+    let (key, query) = (&key[common..], &query[common..]);
+
+    let Some((_, query_rest)) = query.split_first() else {
+        return true;
+    };
+
+    key.starts_with(query_rest)
+        || key.split_first().is_some_and(|(_, key_rest)| {
+            key_rest.starts_with(query_rest) || key_rest.starts_with(query)
+        })
+        || matches!((key, query), ([a, b, key_rest @ ..], [c, d, query_rest @ ..])
+            if a == d && b == c && key_rest.starts_with(query_rest))
+}
+
+/// Searches the bundled cities by name without any network access, closest matches and
+/// then the most populated places first
+pub fn search(query: &str) -> Vec<Place> {
+    search_entries(&ENTRIES, query)
+}
+
+fn search_entries(entries: &[Entry], query: &str) -> Vec<Place> {
+    let query = fold(query.trim());
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let mut found: Vec<(Match, &Entry)> = entries
+        .iter()
+        .filter_map(|entry| Some((matches(&entry.key, &query)?, entry)))
+        .collect();
+
+    found.sort_by(|(a, entry_a), (b, entry_b)| {
+        a.cmp(b)
+            .then(entry_b.place.population.cmp(&entry_a.place.population))
+    });
+
+    found
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, entry)| entry.place.clone())
+        .collect()
+}
+
+/// Great-circle distance between two coordinates in kilometers
+fn distance(latitude_a: f64, longitude_a: f64, latitude_b: f64, longitude_b: f64) -> f64 {
+    let (latitude_a, latitude_b) = (latitude_a.to_radians(), latitude_b.to_radians());
+    let half_latitude = (latitude_b - latitude_a) / 2.0;
+    let half_longitude = (longitude_b - longitude_a).to_radians() / 2.0;
+
+    let a = half_latitude.sin().powi(2)
+        + latitude_a.cos() * latitude_b.cos() * half_longitude.sin().powi(2);
+
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// The bundled city closest to the coordinates, unless all of them are too far away to
+/// name the place
+pub fn nearest(latitude: f64, longitude: f64) -> Option<Place> {
+    nearest_entry(&ENTRIES, latitude, longitude)
+}
+
+fn nearest_entry(entries: &[Entry], latitude: f64, longitude: f64) -> Option<Place> {
+    entries
+        .iter()
+        .map(|entry| {
+            let distance = distance(
+                latitude,
+                longitude,
+                entry.place.latitude,
+                entry.place.longitude,
+            );
+            (distance, entry)
+        })
+        .filter(|(distance, _)| *distance <= MAX_NEAREST_DISTANCE)
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, entry)| entry.place.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lines as `just gazetteer` writes them from the GeoNames dumps, most populated first
+    const CITIES: &str = "\
+New York City\tNew York\tUnited States\t40.7143\t-74.0060\t8804190
+London\tEngland\tUnited Kingdom\t51.5085\t-0.1257\t8961989
+Paris\tÎle-de-France\tFrance\t48.8534\t2.3488\t2138551
+London\tOntario\tCanada\t42.9834\t-81.2330\t346765
+Zürich\tZurich\tSwitzerland\t47.3667\t8.5500\t341730
+Newcastle upon Tyne\tEngland\tUnited Kingdom\t54.9733\t-1.6140\t192382
+Londonderry County Borough\tNorthern Ireland\tUnited Kingdom\t54.9981\t-7.3093\t83652
+Paris\tTexas\tUnited States\t33.6609\t-95.5555\t24782
+Vaduz\t\tLiechtenstein\t47.1415\t9.5215\t5668";
+
+    fn entries() -> Vec<Entry> {
+        CITIES.lines().filter_map(parse_line).collect()
+    }
+
+    fn search_names(query: &str) -> Vec<String> {
+        search_entries(&entries(), query)
+            .into_iter()
+            .map(|place| format!("{}, {}", place.name, place.country.unwrap_or_default()))
+            .collect()
+    }
+
+    fn matches_name(name: &str, query: &str) -> Option<Match> {
+        matches(&fold(name), &fold(query))
+    }
+
+    #[test]
+    fn folds_diacritics_and_ligatures() {
+        assert_eq!(fold("Zürich"), fold("zurich"));
+        assert_eq!(fold("Kraków"), fold("krakow"));
+        assert_eq!(fold("Straße"), fold("strasse"));
+        assert_eq!(fold("Ærøskøbing"), fold("aeroskobing"));
+    }
+
+    #[test]
+    fn ranks_matches() {
+        assert_eq!(matches_name("Berlin", "berlin"), Some(Match::Exact));
+        assert_eq!(matches_name("Berlin", "ber"), Some(Match::Prefix));
+        assert_eq!(
+            matches_name("New York City", "york"),
+            Some(Match::WordPrefix)
+        );
+        assert_eq!(
+            matches_name("Saint-Étienne", "etie"),
+            Some(Match::WordPrefix)
+        );
+        assert_eq!(matches_name("Berlin", "belrin"), Some(Match::Typo));
+        assert_eq!(matches_name("Berlin", "lin"), None);
+        assert!(Match::Exact < Match::Prefix && Match::WordPrefix < Match::Typo);
+    }
+
+    #[test]
+    fn tolerates_typos_only_in_longer_queries() {
+        assert_eq!(matches_name("Bern", "bxrn"), Some(Match::Typo));
+        assert_eq!(matches_name("Bern", "bxr"), None);
+    }
+
+    #[test]
+    fn finds_names_one_edit_away() {
+        let one_edit = |key: &str, query: &str| starts_with_one_edit(&fold(key), &fold(query));
+
+        // Replaced, added, removed and swapped characters
+        assert!(one_edit("london", "lindon"));
+        assert!(one_edit("london", "loondon"));
+        assert!(one_edit("london", "lndon"));
+        assert!(one_edit("london", "lodnon"));
+        // Only the start of the name has to match
+        assert!(one_edit("londonderry", "lomdon"));
+        assert!(one_edit("london", "londonx"));
+
+        assert!(!one_edit("london", "lindin"));
+        assert!(!one_edit("london", "ldnoon"));
+        assert!(!one_edit("paris", "london"));
+    }
+
+    #[test]
+    fn parses_generated_lines() {
+        let entries = entries();
+        assert_eq!(entries.len(), 9);
+
+        let zurich = &entries[4].place;
+        assert_eq!(zurich.name, "Zürich");
+        assert_eq!(zurich.region.as_deref(), Some("Zurich"));
+        assert_eq!(zurich.population, Some(341730));
+
+        // Some countries have no regions
+        assert_eq!(entries[8].place.region, None);
+        assert!(parse_line("Nowhere\t\t\tnorth\teast\t0").is_none());
+    }
+
+    #[test]
+    fn searches_closest_matches_then_most_populated() {
+        assert_eq!(
+            search_names("london"),
+            [
+                "London, United Kingdom",
+                "London, Canada",
+                "Londonderry County Borough, United Kingdom"
+            ]
+        );
+        assert_eq!(
+            search_names("Paris"),
+            ["Paris, France", "Paris, United States"]
+        );
+        assert_eq!(search_names("zurich"), ["Zürich, Switzerland"]);
+        assert_eq!(search_names("york"), ["New York City, United States"]);
+        assert_eq!(
+            search_names("newcastel"),
+            ["Newcastle upon Tyne, United Kingdom"]
+        );
+        assert!(search_names("  ").is_empty());
+    }
+
+    #[test]
+    fn names_coordinates_after_the_nearest_city() {
+        let nearest = |latitude, longitude| {
+            nearest_entry(&entries(), latitude, longitude).map(|place| place.name)
+        };
+
+        assert_eq!(nearest(51.45, -0.2).as_deref(), Some("London"));
+        assert_eq!(nearest(47.2, 9.5).as_deref(), Some("Vaduz"));
+        // The middle of the Atlantic is too far from any city
+        assert_eq!(nearest(40.0, -40.0), None);
+    }
+
+    #[test]
+    fn measures_great_circle_distances() {
+        // Berlin to Paris is about 878 km
+        let berlin_paris = distance(52.52, 13.405, 48.8566, 2.3522);
+        assert!((berlin_paris - 878.0).abs() < 5.0, "{berlin_paris}");
+        assert_eq!(distance(52.52, 13.405, 52.52, 13.405), 0.0);
+    }
+}
@@ -0,0 +1,336 @@
+use reqwest::{Url, header};
+use serde::Deserialize;
+
+use crate::{error::FetchError, http::check_status};
+
+pub const DEFAULT_URL: &str = "https://geocoding-api.open-meteo.com";
+
+/// Nominatim, which unlike the Open-Meteo geocoding API can name coordinates
+pub const DEFAULT_REVERSE_URL: &str = "https://nominatim.openstreetmap.org";
+
+/// Zoom level of reverse lookups, at which Nominatim names the city rather than a street
+const REVERSE_ZOOM: u8 = 10;
+
+/// Number of places requested per search
+pub const MAX_RESULTS: usize = 10;
+
+/// A place found by a location search
+#[derive(Clone, Debug, PartialEq)]
+pub struct Place {
+    pub name: String,
+    /// First-level administrative division, such as a state or region
+    pub region: Option<String>,
+    pub country: Option<String>,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub population: Option<u64>,
+}
+
+impl Place {
+    /// Region and country, to tell apart places with the same name
+    pub fn details(&self) -> String {
+        [&self.region, &self.country]
+            .into_iter()
+            .flatten()
+            .filter(|part| !part.is_empty() && **part != self.name)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Name with region and country, as stored in the config
+    pub fn label(&self) -> String {
+        match self.details() {
+            details if details.is_empty() => self.name.clone(),
+            details => format!("{}, {details}", self.name),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchApi {
+    /// Missing when nothing matches
+    #[serde(default)]
+    results: Vec<SearchResult>,
+}
+
+#[derive(Deserialize)]
+struct SearchResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    admin1: Option<String>,
+    country: Option<String>,
+    population: Option<u64>,
+}
+
+impl From<SearchResult> for Place {
+    fn from(result: SearchResult) -> Self {
+        Place {
+            name: result.name,
+            region: result.admin1,
+            country: result.country,
+            latitude: result.latitude,
+            longitude: result.longitude,
+            population: result.population,
+        }
+    }
+}
+
+/// Nominatim's answer, without an address for coordinates it cannot name
+#[derive(Deserialize)]
+struct ReverseApi {
+    address: Option<Address>,
+}
+
+/// Parts of a Nominatim address, of which only those that apply are present
+#[derive(Deserialize)]
+struct Address {
+    city: Option<String>,
+    town: Option<String>,
+    village: Option<String>,
+    municipality: Option<String>,
+    hamlet: Option<String>,
+    county: Option<String>,
+    state: Option<String>,
+    country: Option<String>,
+}
+
+/// Searches places by name with the Open-Meteo geocoding API, naming them in `language`
+pub async fn search(
+    base_url: &str,
+    user_agent: &str,
+    query: &str,
+    language: &str,
+) -> Result<Vec<Place>, FetchError> {
+    let mut url = Url::parse(&format!("{}/v1/search", base_url.trim_end_matches('/')))
+        .map_err(|_| FetchError::LocationLookup)?;
+    url.query_pairs_mut()
+        .append_pair("name", query.trim())
+        .append_pair("count", &MAX_RESULTS.to_string())
+        .append_pair("language", language)
+        .append_pair("format", "json");
+
+    let request_builder = reqwest::Client::new()
+        .get(url)
+        .header(header::USER_AGENT, user_agent);
+
+    let response = check_status(request_builder.send().await?)?;
+    let data = response.json::<SearchApi>().await?;
+
+    Ok(data.results.into_iter().map(Place::from).collect())
+}
+
+/// Names the place at the coordinates with a Nominatim compatible API, in `language`
+pub async fn reverse(
+    base_url: &str,
+    user_agent: &str,
+    latitude: f64,
+    longitude: f64,
+    language: &str,
+) -> Result<Option<Place>, FetchError> {
+    let mut url = Url::parse(&format!("{}/reverse", base_url.trim_end_matches('/')))
+        .map_err(|_| FetchError::LocationLookup)?;
+    url.query_pairs_mut()
+        .append_pair("lat", &latitude.to_string())
+        .append_pair("lon", &longitude.to_string())
+        .append_pair("zoom", &REVERSE_ZOOM.to_string())
+        .append_pair("accept-language", language)
+        .append_pair("format", "jsonv2");
+
+    let request_builder = reqwest::Client::new()
+        .get(url)
+        .header(header::USER_AGENT, user_agent);
+
+    let response = check_status(request_builder.send().await?)?;
+    let data = response.json::<ReverseApi>().await?;
+
+    Ok(data.address.and_then(|address| {
+        // The most specific settlement, or the wider area outside of any
+        let name = address
+            .city
+            .or(address.town)
+            .or(address.village)
+            .or(address.municipality)
+            .or(address.hamlet)
+            .or(address.county)?;
+
+        Some(Place {
+            name,
+            region: address.state,
+            country: address.country,
+            latitude,
+            longitude,
+            population: None,
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::{self, MockServer};
+
+    const SEARCH: &str = r#"{
+        "results": [
+            {
+                "id": 2950159,
+                "name": "Berlin",
+                "latitude": 52.52437,
+                "longitude": 13.41053,
+                "elevation": 74.0,
+                "feature_code": "PPLC",
+                "country_code": "DE",
+                "timezone": "Europe/Berlin",
+                "population": 3426354,
+                "country": "Deutschland",
+                "admin1": "Berlin"
+            },
+            {
+                "id": 5083330,
+                "name": "Berlin",
+                "latitude": 44.46867,
+                "longitude": -71.18508,
+                "elevation": 311.0,
+                "feature_code": "PPL",
+                "country_code": "US",
+                "timezone": "America/New_York",
+                "population": 9367,
+                "country": "Vereinigte Staaten",
+                "admin1": "New Hampshire"
+            },
+            {
+                "id": 2950096,
+                "name": "Berlin",
+                "latitude": 54.03333,
+                "longitude": 10.45,
+                "feature_code": "PPL",
+                "timezone": "Europe/Berlin"
+            }
+        ],
+        "generationtime_ms": 0.7
+    }"#;
+
+    #[test]
+    fn searches_places_by_name() {
+        let server = MockServer::respond("200 OK", &[], SEARCH);
+
+        let places = mock::block_on(search(
+            &format!("{}/", server.url),
+            "test-agent",
+            " São Paulo ",
+            "de",
+        ))
+        .unwrap();
+        let request = server.request();
+
+        assert!(
+            request.starts_with(
+                "GET /v1/search?name=S%C3%A3o+Paulo&count=10&language=de&format=json "
+            ),
+            "{request}"
+        );
+        assert!(
+            request.to_lowercase().contains("user-agent: test-agent"),
+            "{request}"
+        );
+        assert_eq!(places.len(), 3);
+        assert_eq!(places[1].latitude, 44.46867);
+        assert_eq!(places[1].population, Some(9367));
+    }
+
+    #[test]
+    fn finds_nothing_without_results() {
+        let server = MockServer::respond("200 OK", &[], r#"{"generationtime_ms": 0.4}"#);
+
+        let places = mock::block_on(search(&server.url, "", "Xyzzy", "en")).unwrap();
+
+        assert!(places.is_empty());
+    }
+
+    #[test]
+    fn labels_places_with_region_and_country() {
+        let places: Vec<_> = serde_json::from_str::<SearchApi>(SEARCH)
+            .unwrap()
+            .results
+            .into_iter()
+            .map(Place::from)
+            .collect();
+
+        // The region of a city state repeats its name
+        assert_eq!(places[0].label(), "Berlin, Deutschland");
+        assert_eq!(
+            places[1].label(),
+            "Berlin, New Hampshire, Vereinigte Staaten"
+        );
+        assert_eq!(places[2].details(), "");
+        assert_eq!(places[2].label(), "Berlin");
+    }
+
+    #[test]
+    fn names_coordinates_after_their_settlement() {
+        let server = MockServer::respond(
+            "200 OK",
+            &[],
+            r#"{
+                "place_id": 130880380,
+                "lat": "48.1371079",
+                "lon": "11.5753822",
+                "category": "boundary",
+                "type": "administrative",
+                "name": "München",
+                "address": {
+                    "city": "München",
+                    "state": "Bayern",
+                    "ISO3166-2-lvl4": "DE-BY",
+                    "country": "Deutschland",
+                    "country_code": "de"
+                }
+            }"#,
+        );
+
+        let place = mock::block_on(reverse(&server.url, "test-agent", 48.1372, 11.5755, "de"))
+            .unwrap()
+            .unwrap();
+        let request = server.request();
+
+        assert!(
+            request.starts_with(
+                "GET /reverse?lat=48.1372&lon=11.5755&zoom=10&accept-language=de&format=jsonv2 "
+            ),
+            "{request}"
+        );
+        assert_eq!(place.label(), "München, Bayern, Deutschland");
+        // The coordinates stay those entered, not the settlement's center
+        assert_eq!((place.latitude, place.longitude), (48.1372, 11.5755));
+    }
+
+    #[test]
+    fn names_coordinates_outside_of_settlements_after_the_area() {
+        let cases = [
+            (
+                r#"{"address": {"village": "Hallstatt", "county": "Bezirk Gmunden",
+                    "state": "Oberösterreich", "country": "Österreich"}}"#,
+                Some("Hallstatt"),
+            ),
+            (
+                r#"{"address": {"county": "Highland", "state": "Scotland",
+                    "country": "United Kingdom"}}"#,
+                Some("Highland"),
+            ),
+            (
+                r#"{"address": {"state": "Nunavut", "country": "Canada"}}"#,
+                None,
+            ),
+            (r#"{"error": "Unable to geocode"}"#, None),
+        ];
+
+        for (body, name) in cases {
+            let server = MockServer::respond("200 OK", &[], body);
+
+            let place = mock::block_on(reverse(&server.url, "", 57.0, -4.5, "en")).unwrap();
+
+            assert_eq!(place.map(|place| place.name).as_deref(), name, "{body}");
+        }
+    }
+}
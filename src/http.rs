@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::{Response, StatusCode, header};
+
+use crate::{config::APP_ID, error::FetchError};
+
+/// User-Agent sent with every request, with a contact address when one is configured
+pub fn user_agent(contact: &str) -> String {
+    let version = env!("CARGO_PKG_VERSION");
+
+    match contact.trim() {
+        "" => format!("{APP_ID}/{version}"),
+        contact => format!("{APP_ID}/{version} ({contact})"),
+    }
+}
+
+/// Whether `url` can serve as the base URL of a service
+pub fn is_valid_base_url(url: &str) -> bool {
+    reqwest::Url::parse(url.trim()).is_ok_and(|url| {
+        matches!(url.scheme(), "http" | "https") && url.has_host() && url.query().is_none()
+    })
+}
+
+/// Whether `contact` is empty, an email address or a web address, and fits in a header
+pub fn is_valid_contact(contact: &str) -> bool {
+    let contact = contact.trim();
+
+    if contact.is_empty() {
+        return true;
+    }
+    if header::HeaderValue::from_str(&user_agent(contact)).is_err() {
+        return false;
+    }
+
+    match contact.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty()
+                && !contact.contains(char::is_whitespace)
+                && domain.split('.').filter(|part| !part.is_empty()).count() >= 2
+                && !domain.contains('@')
+        }
+        None => is_valid_base_url(contact),
+    }
+}
+
+/// Parses a header holding an HTTP date such as `Expires` or `Last-Modified`
+pub fn parse_http_date(
+    headers: &header::HeaderMap,
+    name: header::HeaderName,
+) -> Option<DateTime<Utc>> {
+    let value = headers.get(name)?.to_str().ok()?;
+
+    DateTime::parse_from_rfc2822(value)
+        .map(|date| date.with_timezone(&Utc))
+        .ok()
+}
+
+/// Delay requested by a `Retry-After` header, given either in seconds or as a date
+pub fn retry_after(headers: &header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?;
+
+    match value.trim().parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => (parse_http_date(headers, header::RETRY_AFTER)? - Utc::now())
+            .to_std()
+            .ok(),
+    }
+}
+
+/// Turns error status codes into a [`FetchError`], keeping the server's `Retry-After`
+pub fn check_status(response: Response) -> Result<Response, FetchError> {
+    let status = response.status();
+
+    if !status.is_client_error() && !status.is_server_error() {
+        return Ok(response);
+    }
+
+    // Query strings may hold API keys, so keep them out of the logs
+    let mut url = response.url().clone();
+    url.set_query(None);
+    tracing::warn!("{url} responded with {status}");
+
+    Err(match status {
+        StatusCode::TOO_MANY_REQUESTS => FetchError::RateLimited(retry_after(response.headers())),
+        StatusCode::SERVICE_UNAVAILABLE => FetchError::Unavailable(retry_after(response.headers())),
+        status => FetchError::HttpStatus(status.as_u16()),
+    })
+}
+
+/// Local server answering one request with a canned response, for testing clients whose
+/// base URL is configurable
+#[cfg(test)]
+pub mod mock {
+    use std::{
+        future::Future,
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread::{self, JoinHandle},
+    };
+
+    pub struct MockServer {
+        /// Base URL the server is reached at, without a trailing slash
+        pub url: String,
+        request: JoinHandle<String>,
+    }
+
+    impl MockServer {
+        /// Answers the first request with `status`, such as "200 OK", and `body`
+        pub fn respond(status: &str, headers: &[(&str, &str)], body: &str) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+
+            let mut response = format!(
+                "HTTP/1.1 {status}\r\nContent-Length: {}\r\nConnection: close\r\n",
+                body.len()
+            );
+            for (name, value) in headers {
+                response.push_str(&format!("{name}: {value}\r\n"));
+            }
+            response.push_str("\r\n");
+            response.push_str(body);
+
+            let request = thread::spawn(move || {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut head = String::new();
+
+                // Only GET requests are made, their head ends with an empty line
+                let mut reader = BufReader::new(&mut stream);
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    head.push_str(&line);
+                }
+
+                stream.write_all(response.as_bytes()).unwrap();
+                head
+            });
+
+            Self { url, request }
+        }
+
+        /// Request line and headers of the request the server answered
+        pub fn request(self) -> String {
+            self.request.join().unwrap()
+        }
+    }
+
+    /// Runs a request on the runtime reqwest needs
+    pub fn block_on<F: Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_web_addresses_as_base_urls() {
+        assert!(is_valid_base_url("https://api.open-meteo.com"));
+        assert!(is_valid_base_url("https://api.open-meteo.com/"));
+        assert!(is_valid_base_url(" https://weather.example.org/v1/ "));
+        assert!(is_valid_base_url("http://localhost:8080"));
+
+        assert!(!is_valid_base_url(""));
+        assert!(!is_valid_base_url("   "));
+        assert!(!is_valid_base_url("api.open-meteo.com"));
+        assert!(!is_valid_base_url("localhost:8080"));
+        assert!(!is_valid_base_url("ftp://weather.example.org"));
+        assert!(!is_valid_base_url("https://"));
+        assert!(!is_valid_base_url("https://example.org/?key=secret"));
+    }
+
+    #[test]
+    fn accepts_no_contact_an_email_or_a_web_address() {
+        assert!(is_valid_contact(""));
+        assert!(is_valid_contact("  \t "));
+        assert!(is_valid_contact("weather@example.org"));
+        assert!(is_valid_contact(" weather@example.org "));
+        assert!(is_valid_contact("https://example.org/contact"));
+        assert!(is_valid_contact("http://example.org"));
+
+        assert!(!is_valid_contact("weather@localhost"));
+        assert!(!is_valid_contact("@example.org"));
+        assert!(!is_valid_contact("weather @example.org"));
+        assert!(!is_valid_contact("weather@example@org.net"));
+        assert!(!is_valid_contact("example.org"));
+        assert!(!is_valid_contact("weather\n@example.org"));
+    }
+
+    #[test]
+    fn mentions_the_contact_in_the_user_agent() {
+        let version = env!("CARGO_PKG_VERSION");
+
+        assert_eq!(user_agent(" "), format!("{APP_ID}/{version}"));
+        assert_eq!(
+            user_agent(" weather@example.org "),
+            format!("{APP_ID}/{version} (weather@example.org)")
+        );
+    }
+}
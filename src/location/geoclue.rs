@@ -0,0 +1,299 @@
+use std::{future::poll_fn, pin::Pin};
+
+use zbus::{
+    Connection,
+    export::futures_core::Stream,
+    proxy,
+    zvariant::{ObjectPath, OwnedObjectPath},
+};
+
+use super::{AccuracyLevel, Location};
+use crate::{config::APP_ID, error::FetchError};
+
+/// Environment variable selecting the bus GeoClue is reached on, "session" for a mock
+/// service on a private session bus instead of the system service
+pub const BUS_VARIABLE: &str = "COSMIC_WEATHER_GEOCLUE_BUS";
+
+#[proxy(
+    interface = "org.freedesktop.GeoClue2.Manager",
+    default_service = "org.freedesktop.GeoClue2",
+    default_path = "/org/freedesktop/GeoClue2/Manager"
+)]
+trait Manager {
+    /// Client of the calling connection, created on the first call
+    fn get_client(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.GeoClue2.Client",
+    default_service = "org.freedesktop.GeoClue2"
+)]
+trait Client {
+    fn start(&self) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn set_desktop_id(&self, id: &str) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn set_requested_accuracy_level(&self, level: u32) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn set_distance_threshold(&self, meters: u32) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn location_updated(&self, old: ObjectPath<'_>, new: ObjectPath<'_>) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.GeoClue2.Location",
+    default_service = "org.freedesktop.GeoClue2"
+)]
+trait GeoClueLocation {
+    #[zbus(property)]
+    fn latitude(&self) -> zbus::Result<f64>;
+
+    #[zbus(property)]
+    fn longitude(&self) -> zbus::Result<f64>;
+
+    /// Radius of the area the location is in, in meters
+    #[zbus(property)]
+    fn accuracy(&self) -> zbus::Result<f64>;
+}
+
+/// Value of an accuracy level in GeoClue's `GClueAccuracyLevel` enumeration
+fn accuracy_value(accuracy: AccuracyLevel) -> u32 {
+    match accuracy {
+        AccuracyLevel::Country => 1,
+        AccuracyLevel::City => 4,
+        AccuracyLevel::Neighborhood => 5,
+        AccuracyLevel::Street => 6,
+        AccuracyLevel::Exact => 8,
+    }
+}
+
+/// Connects to the bus GeoClue is on, see `BUS_VARIABLE`
+pub async fn connect() -> Result<Connection, FetchError> {
+    let connection = match std::env::var(BUS_VARIABLE).as_deref() {
+        Ok("session") => Connection::session().await?,
+        _ => Connection::system().await?,
+    };
+
+    Ok(connection)
+}
+
+/// A started GeoClue client, which GeoClue stops by itself once the connection is closed
+pub struct GeoClue {
+    connection: Connection,
+    updates: Pin<Box<LocationUpdatedStream>>,
+}
+
+impl GeoClue {
+    pub async fn start(
+        connection: Connection,
+        accuracy: AccuracyLevel,
+        distance_threshold: u32,
+    ) -> Result<Self, FetchError> {
+        let path = ManagerProxy::new(&connection).await?.get_client().await?;
+        let client = ClientProxy::builder(&connection)
+            .path(path)?
+            .build()
+            .await?;
+
+        // GeoClue only serves applications identified by their desktop file
+        client.set_desktop_id(APP_ID).await?;
+        client
+            .set_requested_accuracy_level(accuracy_value(accuracy))
+            .await?;
+        client.set_distance_threshold(distance_threshold).await?;
+
+        // Subscribe before starting so the first location is not missed
+        let updates = Box::pin(client.receive_location_updated().await?);
+        client.start().await?;
+
+        Ok(Self {
+            connection,
+            updates,
+        })
+    }
+
+    /// Waits for the next location, the first one follows shortly after starting
+    pub async fn next_location(&mut self) -> Result<Location, FetchError> {
+        let signal = poll_fn(|cx| self.updates.as_mut().poll_next(cx))
+            .await
+            .ok_or(FetchError::LocationLookup)?;
+        let args = signal.args()?;
+
+        let location = GeoClueLocationProxy::builder(&self.connection)
+            .path(args.new().to_owned())?
+            .build()
+            .await?;
+
+        Ok(Location {
+            latitude: location.latitude().await?,
+            longitude: location.longitude().await?,
+            accuracy: location.accuracy().await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{os::unix::net::UnixStream, time::Duration};
+
+    use futures_lite::future;
+    use zbus::{Guid, connection, interface, object_server::SignalEmitter};
+
+    use super::*;
+    use crate::location::timeout;
+
+    const CLIENT_PATH: &str = "/org/freedesktop/GeoClue2/Client/1";
+    const LOCATION_PATH: &str = "/org/freedesktop/GeoClue2/Location/1";
+
+    struct MockManager;
+
+    #[interface(name = "org.freedesktop.GeoClue2.Manager")]
+    impl MockManager {
+        fn get_client(&self) -> OwnedObjectPath {
+            ObjectPath::from_static_str_unchecked(CLIENT_PATH).into()
+        }
+    }
+
+    /// Client reporting a location on start, unless it is not `located`
+    struct MockClient {
+        located: bool,
+        desktop_id: String,
+        accuracy_level: u32,
+        distance_threshold: u32,
+    }
+
+    #[interface(name = "org.freedesktop.GeoClue2.Client")]
+    impl MockClient {
+        async fn start(&self, #[zbus(signal_emitter)] emitter: SignalEmitter<'_>) {
+            assert_eq!(self.desktop_id, APP_ID);
+            assert_eq!(self.accuracy_level, 4);
+            assert_eq!(self.distance_threshold, 1000);
+
+            if self.located {
+                Self::location_updated(
+                    &emitter,
+                    ObjectPath::from_static_str_unchecked("/"),
+                    ObjectPath::from_static_str_unchecked(LOCATION_PATH),
+                )
+                .await
+                .unwrap();
+            }
+        }
+
+        #[zbus(property)]
+        fn desktop_id(&self) -> String {
+            self.desktop_id.clone()
+        }
+
+        #[zbus(property)]
+        fn set_desktop_id(&mut self, id: String) {
+            self.desktop_id = id;
+        }
+
+        #[zbus(property)]
+        fn requested_accuracy_level(&self) -> u32 {
+            self.accuracy_level
+        }
+
+        #[zbus(property)]
+        fn set_requested_accuracy_level(&mut self, level: u32) {
+            self.accuracy_level = level;
+        }
+
+        #[zbus(property)]
+        fn distance_threshold(&self) -> u32 {
+            self.distance_threshold
+        }
+
+        #[zbus(property)]
+        fn set_distance_threshold(&mut self, meters: u32) {
+            self.distance_threshold = meters;
+        }
+
+        #[zbus(signal)]
+        async fn location_updated(
+            emitter: &SignalEmitter<'_>,
+            old: ObjectPath<'_>,
+            new: ObjectPath<'_>,
+        ) -> zbus::Result<()>;
+    }
+
+    struct MockLocation;
+
+    #[interface(name = "org.freedesktop.GeoClue2.Location")]
+    impl MockLocation {
+        #[zbus(property)]
+        fn latitude(&self) -> f64 {
+            60.17
+        }
+
+        #[zbus(property)]
+        fn longitude(&self) -> f64 {
+            24.94
+        }
+
+        #[zbus(property)]
+        fn accuracy(&self) -> f64 {
+            15000.0
+        }
+    }
+
+    /// Starts a client against a mock GeoClue on a peer-to-peer connection, returning the
+    /// mock's end of the connection to keep it open
+    async fn start_mock(located: bool) -> (Connection, GeoClue) {
+        let (server, client) = UnixStream::pair().unwrap();
+        let mock_client = MockClient {
+            located,
+            desktop_id: String::new(),
+            accuracy_level: 0,
+            distance_threshold: 0,
+        };
+        let server = connection::Builder::async_io_unix_stream(server)
+            .server(Guid::generate())
+            .unwrap()
+            .p2p()
+            .serve_at("/org/freedesktop/GeoClue2/Manager", MockManager)
+            .unwrap()
+            .serve_at(CLIENT_PATH, mock_client)
+            .unwrap()
+            .serve_at(LOCATION_PATH, MockLocation)
+            .unwrap()
+            .build();
+        let connection = connection::Builder::async_io_unix_stream(client)
+            .p2p()
+            .build();
+        let (server, connection) = future::zip(server, connection).await;
+
+        let geoclue = GeoClue::start(connection.unwrap(), AccuracyLevel::City, 1000)
+            .await
+            .unwrap();
+
+        (server.unwrap(), geoclue)
+    }
+
+    #[test]
+    fn reports_the_first_location() {
+        async_io::block_on(async {
+            let (_server, mut geoclue) = start_mock(true).await;
+            let location = geoclue.next_location().await.unwrap();
+
+            assert_eq!(location.latitude, 60.17);
+            assert_eq!(location.longitude, 24.94);
+            assert_eq!(location.accuracy, 15000.0);
+        });
+    }
+
+    #[test]
+    fn gives_up_without_a_first_location() {
+        async_io::block_on(async {
+            let (_server, mut geoclue) = start_mock(false).await;
+            let result = timeout(Duration::from_millis(100), geoclue.next_location()).await;
+
+            assert!(matches!(result, Err(FetchError::LocationLookup)));
+        });
+    }
+}
@@ -0,0 +1,313 @@
+use reqwest::header;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    error::FetchError,
+    http::check_status,
+    provider::{Endpoint, ProviderSettings},
+};
+
+pub const DEFAULT_IPINFO_URL: &str = "https://ipinfo.io/json";
+pub const DEFAULT_IPAPI_CO_URL: &str = "https://ipapi.co/json/";
+pub const DEFAULT_IFCONFIG_URL: &str = "https://ifconfig.co/json";
+
+/// Address echoip, the server behind ifconfig.co, answers on unless configured otherwise
+pub const DEFAULT_SELF_HOSTED_URL: &str = "http://localhost:8080/json";
+
+/// Services locating the device by its public IP address
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IpService {
+    IpInfo,
+    IpApiCo,
+    IfConfig,
+    /// An echoip instance or another service answering like ifconfig.co or ip-api.com
+    SelfHosted,
+}
+
+impl IpService {
+    pub const ALL: [Self; 4] = [
+        Self::IpInfo,
+        Self::IpApiCo,
+        Self::IfConfig,
+        Self::SelfHosted,
+    ];
+
+    /// Public services, tried in this order unless configured otherwise
+    pub const DEFAULT: [Self; 3] = [Self::IpInfo, Self::IpApiCo, Self::IfConfig];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::IpInfo => "ipinfo.io",
+            Self::IpApiCo => "ipapi.co",
+            Self::IfConfig => "ifconfig.co",
+            Self::SelfHosted => "self-hosted",
+        }
+    }
+
+    async fn lookup(self, url: &str, user_agent: &str) -> Result<IpLocation, FetchError> {
+        let request_builder = reqwest::Client::new()
+            .get(url)
+            .header(header::USER_AGENT, user_agent)
+            .header(header::ACCEPT, "application/json");
+
+        let response = check_status(request_builder.send().await?)?;
+
+        self.parse(&response.text().await?)
+    }
+
+    /// Reads the service's answer, which does not parse when the URL points at something
+    /// else than the service
+    fn parse(self, body: &str) -> Result<IpLocation, FetchError> {
+        fn decode<T: DeserializeOwned>(body: &str) -> Result<T, FetchError> {
+            serde_json::from_str(body).map_err(|e| {
+                tracing::debug!("Unexpected IP location response: {e}");
+                FetchError::Parse
+            })
+        }
+
+        // Lookups that cannot be resolved, such as of private addresses, are answered
+        // without coordinates
+        let location = match self {
+            Self::IpInfo => decode::<IpInfo>(body)?.into_location(),
+            Self::IpApiCo => decode::<IpApiCo>(body)?.into_location(),
+            Self::IfConfig | Self::SelfHosted => decode::<EchoIp>(body)?.into_location(),
+        };
+
+        location.ok_or(FetchError::LocationLookup)
+    }
+}
+
+/// Location of the device's public IP address
+#[derive(Clone, Debug)]
+pub struct IpLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub city: String,
+    pub region: String,
+    /// Radius of the area the device is in, in meters, when the service reports it
+    pub accuracy: Option<f64>,
+}
+
+/// ipinfo.io's answer, with the coordinates joined as "latitude,longitude"
+#[derive(Deserialize)]
+struct IpInfo {
+    loc: Option<String>,
+    city: Option<String>,
+    region: Option<String>,
+}
+
+impl IpInfo {
+    fn into_location(self) -> Option<IpLocation> {
+        let (latitude, longitude) = self.loc.as_deref()?.split_once(',')?;
+
+        Some(IpLocation {
+            latitude: latitude.trim().parse().ok()?,
+            longitude: longitude.trim().parse().ok()?,
+            city: self.city.unwrap_or_default(),
+            region: self.region.unwrap_or_default(),
+            accuracy: None,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct IpApiCo {
+    latitude: Option<f64>,
+    longitude: Option<f64>,
+    city: Option<String>,
+    region: Option<String>,
+}
+
+impl IpApiCo {
+    fn into_location(self) -> Option<IpLocation> {
+        Some(IpLocation {
+            latitude: self.latitude?,
+            longitude: self.longitude?,
+            city: self.city.unwrap_or_default(),
+            region: self.region.unwrap_or_default(),
+            accuracy: None,
+        })
+    }
+}
+
+/// Answer of echoip, which ifconfig.co runs, or of ip-api.com, which the applet used to
+/// look the location up with and whose mirrors may still be configured
+#[derive(Deserialize)]
+struct EchoIp {
+    #[serde(alias = "lat")]
+    latitude: Option<f64>,
+    #[serde(alias = "lon")]
+    longitude: Option<f64>,
+    city: Option<String>,
+    #[serde(alias = "regionName")]
+    region_name: Option<String>,
+    /// MaxMind's accuracy radius in kilometers, which echoip itself leaves out but
+    /// self-hosted services built on the same database can pass on
+    accuracy_radius: Option<f64>,
+}
+
+impl EchoIp {
+    fn into_location(self) -> Option<IpLocation> {
+        Some(IpLocation {
+            latitude: self.latitude?,
+            longitude: self.longitude?,
+            city: self.city.unwrap_or_default(),
+            region: self.region_name.unwrap_or_default(),
+            accuracy: self.accuracy_radius.map(|radius| radius * 1000.0),
+        })
+    }
+}
+
+/// Looks the location up with each of `services` in turn until one of them answers
+pub async fn locate(
+    services: &[IpService],
+    settings: &ProviderSettings,
+) -> Result<IpLocation, FetchError> {
+    let user_agent = settings.user_agent();
+    let mut error = FetchError::LocationLookup;
+
+    for service in services {
+        let url = Endpoint::IpService(*service).url(settings);
+
+        match service.lookup(url, &user_agent).await {
+            Ok(location) => return Ok(location),
+            Err(e) => {
+                tracing::warn!("IP location lookup with {} failed: {e}", service.name());
+                error = e;
+            }
+        }
+    }
+
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::{self, MockServer};
+
+    const IPINFO: &str = r#"{"ip":"8.8.8.8","hostname":"dns.google","city":"Mountain View",
+        "region":"California","country":"US","loc":"37.4056,-122.0775",
+        "org":"AS15169 Google LLC","postal":"94043","timezone":"America/Los_Angeles",
+        "readme":"https://ipinfo.io/missingauth","anycast":true}"#;
+
+    const IPAPI_CO: &str = r#"{"ip":"8.8.8.8","network":"8.8.8.0/24","version":"IPv4",
+        "city":"Mountain View","region":"California","region_code":"CA","country":"US",
+        "country_name":"United States","latitude":37.42301,"longitude":-122.083352,
+        "timezone":"America/Los_Angeles","utc_offset":"-0700","asn":"AS15169","org":"GOOGLE"}"#;
+
+    const ECHOIP: &str = r#"{"ip":"203.0.113.7","ip_decimal":3405803783,"country":"Norway",
+        "country_iso":"NO","country_eu":false,"region_name":"Oslo","region_code":"03",
+        "zip_code":"0171","city":"Oslo","latitude":59.9127,"longitude":10.7461,
+        "time_zone":"Europe/Oslo","asn":"AS2119","asn_org":"Telenor Norge AS"}"#;
+
+    const IP_API: &str = r#"{"status":"success","country":"Norway","countryCode":"NO",
+        "region":"03","regionName":"Oslo","city":"Oslo","zip":"0171","lat":59.9127,
+        "lon":10.7461,"timezone":"Europe/Oslo","isp":"Telenor Norge AS","org":"",
+        "as":"AS2119 Telenor Norge AS","query":"203.0.113.7"}"#;
+
+    #[test]
+    fn parses_ipinfo() {
+        let location = IpService::IpInfo.parse(IPINFO).unwrap();
+
+        assert_eq!(
+            (location.latitude, location.longitude),
+            (37.4056, -122.0775)
+        );
+        assert_eq!(location.city, "Mountain View");
+        assert_eq!(location.region, "California");
+        assert_eq!(location.accuracy, None);
+    }
+
+    #[test]
+    fn parses_ipapi_co() {
+        let location = IpService::IpApiCo.parse(IPAPI_CO).unwrap();
+
+        assert_eq!(
+            (location.latitude, location.longitude),
+            (37.42301, -122.083352)
+        );
+        assert_eq!(location.city, "Mountain View");
+        assert_eq!(location.region, "California");
+    }
+
+    #[test]
+    fn parses_echoip_and_ip_api() {
+        for body in [ECHOIP, IP_API] {
+            let location = IpService::IfConfig.parse(body).unwrap();
+
+            assert_eq!((location.latitude, location.longitude), (59.9127, 10.7461));
+            assert_eq!(location.city, "Oslo");
+            assert_eq!(location.region, "Oslo");
+            assert_eq!(location.accuracy, None);
+        }
+    }
+
+    #[test]
+    fn parses_the_accuracy_radius_in_meters() {
+        let body = ECHOIP.replace(
+            r#""city":"Oslo","#,
+            r#""city":"Oslo","accuracy_radius":20,"#,
+        );
+        let location = IpService::SelfHosted.parse(&body).unwrap();
+
+        assert_eq!(location.accuracy, Some(20_000.0));
+    }
+
+    #[test]
+    fn tells_unresolved_lookups_from_unexpected_answers() {
+        // Private addresses are answered without coordinates
+        let unresolved = [
+            (IpService::IpInfo, r#"{"ip":"127.0.0.1","bogon":true}"#),
+            (
+                IpService::IpApiCo,
+                r#"{"ip":"127.0.0.1","error":true,"reason":"Reserved IP Address"}"#,
+            ),
+            (IpService::IpInfo, r#"{"ip":"8.8.8.8","loc":"somewhere"}"#),
+        ];
+        for (service, body) in unresolved {
+            assert_eq!(service.parse(body).unwrap_err(), FetchError::LocationLookup);
+        }
+
+        // A URL pointing at something else than the service
+        assert_eq!(
+            IpService::SelfHosted
+                .parse("<html><body>It works!</body></html>")
+                .unwrap_err(),
+            FetchError::Parse
+        );
+        assert_eq!(
+            IpService::IfConfig
+                .parse(r#"{"latitude":"north"}"#)
+                .unwrap_err(),
+            FetchError::Parse
+        );
+    }
+
+    #[test]
+    fn tries_the_next_service_after_a_failure() {
+        let misconfigured = MockServer::respond("200 OK", &[], "<html></html>");
+        let self_hosted = MockServer::respond("200 OK", &[], ECHOIP);
+        let settings = ProviderSettings {
+            ipinfo_url: misconfigured.url.clone(),
+            self_hosted_ip_url: self_hosted.url.clone(),
+            ..ProviderSettings::default()
+        };
+
+        let location = mock::block_on(locate(
+            &[IpService::IpInfo, IpService::SelfHosted],
+            &settings,
+        ))
+        .unwrap();
+        assert_eq!(location.city, "Oslo");
+
+        // The last failure is reported when no service answers
+        let misconfigured = MockServer::respond("200 OK", &[], "<html></html>");
+        let settings = ProviderSettings {
+            self_hosted_ip_url: misconfigured.url.clone(),
+            ..ProviderSettings::default()
+        };
+        let error = mock::block_on(locate(&[IpService::SelfHosted], &settings)).unwrap_err();
+        assert_eq!(error, FetchError::Parse);
+    }
+}
@@ -0,0 +1,125 @@
+pub mod geoclue;
+pub mod ip;
+pub mod portal;
+
+use std::{future::Future, path::Path, time::Duration};
+
+use async_io::Timer;
+use futures_lite::FutureExt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::FetchError;
+use geoclue::GeoClue;
+use portal::Portal;
+
+/// Longest wait for the first location after starting, GeoClue or the portal may not
+/// find any and the fallback is used instead
+pub const FIRST_LOCATION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fails with [`FetchError::LocationLookup`] unless `future` completes within `duration`
+pub async fn timeout<T>(
+    duration: Duration,
+    future: impl Future<Output = Result<T, FetchError>>,
+) -> Result<T, FetchError> {
+    future
+        .or(async {
+            Timer::after(duration).await;
+            Err(FetchError::LocationLookup)
+        })
+        .await
+}
+
+/// Where the location is detected from when auto-detecting it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LocationSource {
+    /// The GeoClue service, falling back to the IP address when it is unavailable
+    GeoClue,
+    /// The xdg-desktop-portal Location portal, which asks the user for permission
+    Portal,
+    IpAddress,
+}
+
+impl LocationSource {
+    pub const ALL: [Self; 3] = [Self::GeoClue, Self::Portal, Self::IpAddress];
+}
+
+impl Default for LocationSource {
+    /// The portal inside the Flatpak sandbox, which cannot reach GeoClue directly
+    fn default() -> Self {
+        if Path::new("/.flatpak-info").exists() {
+            Self::Portal
+        } else {
+            Self::GeoClue
+        }
+    }
+}
+
+/// How precisely the device is located, coarser levels can be answered without GPS
+/// or Wi-Fi scanning
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccuracyLevel {
+    Country,
+    #[default]
+    City,
+    Neighborhood,
+    Street,
+    Exact,
+}
+
+impl AccuracyLevel {
+    pub const ALL: [Self; 5] = [
+        Self::Country,
+        Self::City,
+        Self::Neighborhood,
+        Self::Street,
+        Self::Exact,
+    ];
+}
+
+/// A detected location of the device
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Radius of the area the location is in, in meters
+    pub accuracy: f64,
+}
+
+/// A started location source reporting the device's location as it changes
+pub enum Locator {
+    GeoClue(GeoClue),
+    Portal(Portal),
+}
+
+impl Locator {
+    /// Starts locating with `source`, reporting moves further than `distance_threshold`
+    /// meters
+    pub async fn start(
+        source: LocationSource,
+        accuracy: AccuracyLevel,
+        distance_threshold: u32,
+    ) -> Result<Self, FetchError> {
+        match source {
+            LocationSource::GeoClue => {
+                let connection = geoclue::connect().await?;
+                let geoclue = GeoClue::start(connection, accuracy, distance_threshold).await?;
+                Ok(Self::GeoClue(geoclue))
+            }
+            LocationSource::Portal => {
+                let connection = zbus::Connection::session().await?;
+                let portal = Portal::start(connection, accuracy, distance_threshold).await?;
+                Ok(Self::Portal(portal))
+            }
+            // The IP address is looked up with every refresh instead
+            LocationSource::IpAddress => Err(FetchError::LocationLookup),
+        }
+    }
+
+    /// Waits for the next location, the first one follows shortly after starting
+    pub async fn next_location(&mut self) -> Result<Location, FetchError> {
+        match self {
+            Self::GeoClue(geoclue) => geoclue.next_location().await,
+            Self::Portal(portal) => portal.next_location().await,
+        }
+    }
+}
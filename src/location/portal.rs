@@ -0,0 +1,332 @@
+use std::{collections::HashMap, future::poll_fn, pin::Pin, time::Duration};
+
+use zbus::{
+    Connection,
+    export::futures_core::Stream,
+    proxy,
+    zvariant::{ObjectPath, OwnedObjectPath, Value},
+};
+
+use super::{AccuracyLevel, Location, timeout};
+use crate::error::FetchError;
+
+/// Longest wait for the answer to starting a session, which includes the user deciding
+/// on the permission dialog
+const START_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[proxy(
+    interface = "org.freedesktop.portal.Location",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait LocationPortal {
+    fn create_session(&self, options: HashMap<&str, Value<'_>>) -> zbus::Result<OwnedObjectPath>;
+
+    fn start(
+        &self,
+        session_handle: &ObjectPath<'_>,
+        parent_window: &str,
+        options: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn location_updated(
+        &self,
+        session_handle: ObjectPath<'_>,
+        location: HashMap<&str, Value<'_>>,
+    ) -> zbus::Result<()>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.portal.Request",
+    default_service = "org.freedesktop.portal.Desktop"
+)]
+trait Request {
+    #[zbus(signal)]
+    fn response(&self, response: u32, results: HashMap<&str, Value<'_>>) -> zbus::Result<()>;
+}
+
+/// Value of an accuracy level in the portal's accuracy enumeration
+fn accuracy_value(accuracy: AccuracyLevel) -> u32 {
+    match accuracy {
+        AccuracyLevel::Country => 1,
+        AccuracyLevel::City => 2,
+        AccuracyLevel::Neighborhood => 3,
+        AccuracyLevel::Street => 4,
+        AccuracyLevel::Exact => 5,
+    }
+}
+
+/// Path of the request object the portal answers a call made with `token` on
+fn request_path(connection: &Connection, token: &str) -> Result<OwnedObjectPath, FetchError> {
+    let sender = connection
+        .unique_name()
+        .ok_or(FetchError::LocationLookup)?
+        .trim_start_matches(':')
+        .replace('.', "_");
+
+    OwnedObjectPath::try_from(format!(
+        "/org/freedesktop/portal/desktop/request/{sender}/{token}"
+    ))
+    .map_err(|_| FetchError::LocationLookup)
+}
+
+/// A started Location portal session, which the portal closes by itself once the
+/// connection is closed
+pub struct Portal {
+    session: OwnedObjectPath,
+    updates: Pin<Box<LocationUpdatedStream>>,
+}
+
+impl Portal {
+    /// Starts a session, asking the user for permission unless it was given before
+    pub async fn start(
+        connection: Connection,
+        accuracy: AccuracyLevel,
+        distance_threshold: u32,
+    ) -> Result<Self, FetchError> {
+        Self::start_within(connection, accuracy, distance_threshold, START_TIMEOUT).await
+    }
+
+    /// Starts a session, giving up when the portal has not answered within `start_timeout`
+    async fn start_within(
+        connection: Connection,
+        accuracy: AccuracyLevel,
+        distance_threshold: u32,
+        start_timeout: Duration,
+    ) -> Result<Self, FetchError> {
+        let portal = LocationPortalProxy::new(&connection).await?;
+        let token = format!("cosmic_weather_{}", fastrand::u32(..));
+
+        let session = portal
+            .create_session(HashMap::from([
+                ("session_handle_token", Value::from(token.as_str())),
+                ("distance-threshold", Value::from(distance_threshold)),
+                ("accuracy", Value::from(accuracy_value(accuracy))),
+            ]))
+            .await?;
+        let updates = Box::pin(portal.receive_location_updated().await?);
+
+        // Subscribe to the answer before starting, it may come before the call returns
+        let request = RequestProxy::builder(&connection)
+            .path(request_path(&connection, &token)?)?
+            .build()
+            .await?;
+        let mut responses = Box::pin(request.receive_response().await?);
+
+        portal
+            .start(
+                &session,
+                "",
+                HashMap::from([("handle_token", Value::from(token.as_str()))]),
+            )
+            .await?;
+
+        // A portal without a location backend may never answer
+        let response = timeout(start_timeout, async {
+            poll_fn(|cx| responses.as_mut().poll_next(cx))
+                .await
+                .ok_or(FetchError::LocationLookup)
+        })
+        .await?;
+
+        match response.args()?.response() {
+            0 => Ok(Self { session, updates }),
+            // The user did not allow access to the location
+            1 => Err(FetchError::LocationDenied),
+            _ => Err(FetchError::LocationLookup),
+        }
+    }
+
+    /// Waits for the next location, the first one follows shortly after starting
+    pub async fn next_location(&mut self) -> Result<Location, FetchError> {
+        loop {
+            let signal = poll_fn(|cx| self.updates.as_mut().poll_next(cx))
+                .await
+                .ok_or(FetchError::LocationLookup)?;
+            let args = signal.args()?;
+
+            // Every session's updates are sent to the same interface
+            if args.session_handle().as_str() != self.session.as_str() {
+                continue;
+            }
+
+            let field = |name| {
+                args.location()
+                    .get(name)
+                    .and_then(|value| value.downcast_ref::<f64>().ok())
+                    .ok_or(FetchError::Parse)
+            };
+
+            return Ok(Location {
+                latitude: field("Latitude")?,
+                longitude: field("Longitude")?,
+                accuracy: field("Accuracy")?,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixStream;
+
+    use futures_lite::future;
+    use zbus::{
+        Guid, connection, interface, names::BusName, object_server::SignalEmitter,
+        zvariant::OwnedValue,
+    };
+
+    use super::*;
+
+    const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+
+    /// Unique name the applet's connection is given, as the bus would
+    const SENDER: &str = ":1.23";
+
+    fn location(latitude: f64, longitude: f64) -> HashMap<&'static str, Value<'static>> {
+        HashMap::from([
+            ("Latitude", Value::from(latitude)),
+            ("Longitude", Value::from(longitude)),
+            ("Accuracy", Value::from(15000.0)),
+            ("Altitude", Value::from(-1.7976931348623157e308)),
+        ])
+    }
+
+    /// Portal answering the request to start with `response`, or never without one
+    struct MockPortal {
+        response: Option<u32>,
+    }
+
+    #[interface(name = "org.freedesktop.portal.Location")]
+    impl MockPortal {
+        fn create_session(&self, options: HashMap<String, OwnedValue>) -> OwnedObjectPath {
+            assert_eq!(options["accuracy"].downcast_ref::<u32>(), Ok(2));
+            assert_eq!(
+                options["distance-threshold"].downcast_ref::<u32>(),
+                Ok(1000)
+            );
+
+            let token = options["session_handle_token"]
+                .downcast_ref::<&str>()
+                .unwrap();
+            OwnedObjectPath::try_from(format!("{PORTAL_PATH}/session/1_23/{token}")).unwrap()
+        }
+
+        async fn start(
+            &self,
+            session_handle: ObjectPath<'_>,
+            _parent_window: &str,
+            options: HashMap<String, OwnedValue>,
+            #[zbus(connection)] connection: &Connection,
+            #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        ) -> OwnedObjectPath {
+            let token = options["handle_token"].downcast_ref::<&str>().unwrap();
+            let request =
+                OwnedObjectPath::try_from(format!("{PORTAL_PATH}/request/1_23/{token}")).unwrap();
+
+            if let Some(response) = self.response {
+                connection
+                    .emit_signal(
+                        None::<BusName<'_>>,
+                        &request,
+                        "org.freedesktop.portal.Request",
+                        "Response",
+                        &(response, HashMap::<&str, Value<'_>>::new()),
+                    )
+                    .await
+                    .unwrap();
+            }
+
+            if self.response == Some(0) {
+                // Another application's session, which has to be skipped
+                let other = ObjectPath::from_static_str_unchecked(
+                    "/org/freedesktop/portal/desktop/session/1_42/other",
+                );
+                Self::location_updated(&emitter, other, location(0.0, 0.0))
+                    .await
+                    .unwrap();
+                Self::location_updated(&emitter, session_handle, location(60.17, 24.94))
+                    .await
+                    .unwrap();
+            }
+
+            request
+        }
+
+        #[zbus(signal)]
+        async fn location_updated(
+            emitter: &SignalEmitter<'_>,
+            session_handle: ObjectPath<'_>,
+            location: HashMap<&str, Value<'_>>,
+        ) -> zbus::Result<()>;
+    }
+
+    /// Starts a session with a mock portal on a peer-to-peer connection, returning the
+    /// mock's end of the connection to keep it open
+    async fn start_mock(
+        response: Option<u32>,
+        start_timeout: Duration,
+    ) -> (Connection, Result<Portal, FetchError>) {
+        // Only the side accepting the connection can be given a unique name
+        let (server, client) = UnixStream::pair().unwrap();
+        let server = connection::Builder::async_io_unix_stream(server)
+            .p2p()
+            .serve_at(PORTAL_PATH, MockPortal { response })
+            .unwrap()
+            .build();
+        let connection = connection::Builder::async_io_unix_stream(client)
+            .server(Guid::generate())
+            .unwrap()
+            .p2p()
+            .unique_name(SENDER)
+            .unwrap()
+            .build();
+        let (server, connection) = future::zip(server, connection).await;
+
+        let portal = Portal::start_within(
+            connection.unwrap(),
+            AccuracyLevel::City,
+            1000,
+            start_timeout,
+        )
+        .await;
+
+        (server.unwrap(), portal)
+    }
+
+    #[test]
+    fn reports_locations_of_its_own_session() {
+        async_io::block_on(async {
+            let (_server, portal) = start_mock(Some(0), START_TIMEOUT).await;
+            let Ok(mut portal) = portal else {
+                panic!("the session did not start");
+            };
+            let location = portal.next_location().await.unwrap();
+
+            assert_eq!(location.latitude, 60.17);
+            assert_eq!(location.longitude, 24.94);
+            assert_eq!(location.accuracy, 15000.0);
+        });
+    }
+
+    #[test]
+    fn reports_denied_access() {
+        async_io::block_on(async {
+            let (_server, portal) = start_mock(Some(1), START_TIMEOUT).await;
+            assert_eq!(portal.err(), Some(FetchError::LocationDenied));
+
+            // The dialog was closed in another way
+            let (_server, portal) = start_mock(Some(2), START_TIMEOUT).await;
+            assert_eq!(portal.err(), Some(FetchError::LocationLookup));
+        });
+    }
+
+    #[test]
+    fn gives_up_without_an_answer() {
+        async_io::block_on(async {
+            let (_server, portal) = start_mock(None, Duration::from_millis(100)).await;
+            assert_eq!(portal.err(), Some(FetchError::LocationLookup));
+        });
+    }
+}
@@ -1,6 +1,16 @@
 pub mod applet;
+pub mod cache;
 pub mod config;
+pub mod coordinates;
+pub mod error;
+pub mod gazetteer;
+pub mod geocoding;
+pub mod http;
 pub mod i18n;
+pub mod location;
+pub mod provider;
+pub mod retry;
+pub mod units;
 pub mod weather;
 
 fn main() -> cosmic::iced::Result {
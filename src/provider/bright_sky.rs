@@ -0,0 +1,410 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use reqwest::header;
+use serde::{Deserialize, de::DeserializeOwned};
+
+use super::{ForecastResponse, ProviderFuture, ProviderKind, WeatherProvider};
+use crate::{
+    error::FetchError,
+    http::check_status,
+    weather::{Attribution, Conditions, DailyForecast, Forecast},
+};
+
+pub const DEFAULT_URL: &str = "https://api.brightsky.dev";
+
+/// Number of days requested, matching what the popup shows
+const FORECAST_DAYS: i64 = 9;
+
+/// Stations report observations every 10 to 30 minutes
+const REFRESH_INTERVAL: Duration = Duration::minutes(15);
+
+/// Timezone records are requested in and days are split in, DWD's sources are in and
+/// around Germany
+const TIMEZONE: &str = "Europe/Berlin";
+
+/// Hourly records, observations for past hours and MOSMIX forecasts for the following ones
+#[derive(Deserialize)]
+struct WeatherApi {
+    weather: Vec<Record>,
+}
+
+#[derive(Deserialize)]
+struct Record {
+    /// In [`TIMEZONE`]
+    timestamp: DateTime<FixedOffset>,
+    temperature: Option<f32>,
+    pressure_msl: Option<f32>,
+    relative_humidity: Option<f32>,
+    cloud_cover: Option<f32>,
+    /// In km/h
+    wind_speed: Option<f32>,
+    wind_direction: Option<f32>,
+    precipitation: Option<f32>,
+    precipitation_probability: Option<f32>,
+    condition: Option<String>,
+    icon: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CurrentWeatherApi {
+    weather: CurrentRecord,
+    sources: Vec<Source>,
+}
+
+/// Latest observation of the nearest station, aggregated over the last 10 to 60 minutes
+#[derive(Deserialize)]
+struct CurrentRecord {
+    timestamp: DateTime<Utc>,
+    source_id: i64,
+    temperature: Option<f32>,
+    pressure_msl: Option<f32>,
+    relative_humidity: Option<f32>,
+    cloud_cover: Option<f32>,
+    /// In km/h
+    wind_speed_10: Option<f32>,
+    wind_direction_10: Option<f32>,
+    condition: Option<String>,
+    icon: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Source {
+    id: i64,
+    station_name: Option<String>,
+}
+
+impl Record {
+    fn icon(&self) -> &'static str {
+        condition_to_icon(self.icon.as_deref(), self.condition.as_deref())
+    }
+
+    fn conditions(&self) -> Option<Conditions> {
+        Some(Conditions {
+            time: self.timestamp.to_utc(),
+            temperature: self.temperature?,
+            apparent_temperature: None,
+            icon: self.icon().to_string(),
+            uv_index: None,
+            pressure: self.pressure_msl,
+            humidity: self.relative_humidity,
+            cloud_cover: self.cloud_cover,
+            wind_speed: self.wind_speed.map(|speed| speed / 3.6),
+            wind_direction: self.wind_direction,
+            precipitation: self.precipitation,
+            precipitation_probability: self.precipitation_probability,
+            precipitation_min: None,
+            precipitation_max: None,
+        })
+    }
+}
+
+impl CurrentWeatherApi {
+    fn station_name(&self) -> Option<String> {
+        self.sources
+            .iter()
+            .find(|source| source.id == self.weather.source_id)
+            .and_then(|source| source.station_name.clone())
+    }
+
+    /// Observed conditions, with the forecast precipitation of the current hour
+    fn conditions(&self, hourly: &[Conditions]) -> Option<Conditions> {
+        let weather = &self.weather;
+        let hour = hourly.iter().find(|entry| {
+            entry.time <= weather.timestamp && weather.timestamp < entry.time + Duration::hours(1)
+        });
+
+        Some(Conditions {
+            time: weather.timestamp,
+            temperature: weather.temperature?,
+            apparent_temperature: None,
+            icon: condition_to_icon(weather.icon.as_deref(), weather.condition.as_deref())
+                .to_string(),
+            uv_index: None,
+            pressure: weather.pressure_msl,
+            humidity: weather.relative_humidity,
+            cloud_cover: weather.cloud_cover,
+            wind_speed: weather.wind_speed_10.map(|speed| speed / 3.6),
+            wind_direction: weather.wind_direction_10,
+            precipitation: hour.and_then(|hour| hour.precipitation),
+            precipitation_probability: hour.and_then(|hour| hour.precipitation_probability),
+            precipitation_min: None,
+            precipitation_max: None,
+        })
+    }
+}
+
+/// Aggregates the hourly records by calendar day in the timezone they were requested in
+fn daily(records: &[Record]) -> Vec<DailyForecast> {
+    let mut by_date: BTreeMap<NaiveDate, Vec<&Record>> = BTreeMap::new();
+
+    for record in records {
+        by_date
+            .entry(record.timestamp.date_naive())
+            .or_default()
+            .push(record);
+    }
+
+    by_date
+        .into_iter()
+        .filter_map(|(date, records)| {
+            let temperatures = records.iter().filter_map(|record| record.temperature);
+
+            // Count day and night icons together, the daily icon always uses the day variant
+            let mut icon_counts: BTreeMap<&str, usize> = BTreeMap::new();
+            for record in &records {
+                let icon = condition_to_icon(
+                    record
+                        .icon
+                        .as_deref()
+                        .map(|icon| icon.replace("-night", "-day"))
+                        .as_deref(),
+                    record.condition.as_deref(),
+                );
+                *icon_counts.entry(icon).or_default() += 1;
+            }
+
+            Some(DailyForecast {
+                date,
+                min_temperature: temperatures.clone().reduce(f32::min)?,
+                max_temperature: temperatures.reduce(f32::max)?,
+                icon: icon_counts
+                    .into_iter()
+                    .max_by_key(|(_, count)| *count)
+                    .map_or("weather-clear", |(icon, _)| icon)
+                    .to_string(),
+                precipitation: records
+                    .iter()
+                    .filter_map(|record| record.precipitation)
+                    .sum(),
+                precipitation_probability: records
+                    .iter()
+                    .filter_map(|record| record.precipitation_probability)
+                    .reduce(f32::max),
+                max_wind_speed: records
+                    .iter()
+                    .filter_map(|record| record.wind_speed)
+                    .fold(0.0, f32::max)
+                    / 3.6,
+            })
+        })
+        .collect()
+}
+
+/// Client for the Bright Sky API, which serves DWD forecasts and station observations
+#[derive(Clone)]
+pub struct BrightSky {
+    client: reqwest::Client,
+    base_url: String,
+    user_agent: String,
+}
+
+impl BrightSky {
+    pub fn new(base_url: &str, user_agent: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            user_agent,
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(&self, url: String) -> Result<T, FetchError> {
+        let request_builder = self
+            .client
+            .get(url)
+            .header(header::USER_AGENT, &self.user_agent);
+
+        let response = check_status(request_builder.send().await?)
+            // Bright Sky only has sources in and around Germany
+            .map_err(|e| match e {
+                FetchError::HttpStatus(404) => FetchError::UnsupportedLocation,
+                e => e,
+            })?;
+
+        Ok(response.json::<T>().await?)
+    }
+
+    async fn get_forecast(
+        self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<ForecastResponse, FetchError> {
+        let location = format!("lat={latitude:.4}&lon={longitude:.4}");
+        // Dates are read in the timezone too, which is never behind UTC, so starting on
+        // the UTC date always includes the whole local day
+        let today = Utc::now().date_naive();
+        let last_date = today + Duration::days(FORECAST_DAYS);
+
+        let weather = self
+            .get::<WeatherApi>(format!(
+                "{}/weather?{location}&date={today}&last_date={last_date}&tz={TIMEZONE}",
+                self.base_url
+            ))
+            .await?;
+
+        // Not every location has a station with recent observations nearby
+        let current = self
+            .get::<CurrentWeatherApi>(format!("{}/current_weather?{location}", self.base_url))
+            .await
+            .inspect_err(|e| tracing::warn!("Failed to get current weather: {e}"))
+            .ok();
+
+        let hourly: Vec<_> = weather
+            .weather
+            .iter()
+            .filter_map(Record::conditions)
+            .collect();
+
+        let forecast = Forecast {
+            current: current
+                .as_ref()
+                .and_then(|current| current.conditions(&hourly)),
+            daily: daily(&weather.weather),
+            hourly,
+            alerts: Vec::new(),
+            attribution: Attribution {
+                name: String::from("Deutscher Wetterdienst via Bright Sky"),
+                url: String::from("https://brightsky.dev/"),
+            },
+            station: current.and_then(|current| current.station_name()),
+        };
+
+        Ok(ForecastResponse {
+            forecast: Some(forecast),
+            expires: Some(Utc::now() + REFRESH_INTERVAL),
+        })
+    }
+}
+
+impl WeatherProvider for BrightSky {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::BrightSky
+    }
+
+    fn forecast(&self, latitude: f64, longitude: f64) -> ProviderFuture<ForecastResponse> {
+        Box::pin(self.clone().get_forecast(latitude, longitude))
+    }
+}
+
+/// Maps Bright Sky icons, or its condition when there is no icon, to the icon
+/// names used for met.no symbols
+fn condition_to_icon(icon: Option<&str>, condition: Option<&str>) -> &'static str {
+    match icon {
+        Some("clear-night") => return "weather-clear-night",
+        Some("partly-cloudy-day") => return "weather-few-clouds",
+        Some("partly-cloudy-night") => return "weather-few-clouds-night",
+        Some("cloudy") => return "weather-overcast",
+        Some("fog") => return "weather-fog",
+        Some("rain" | "sleet" | "hail") => return "weather-showers",
+        Some("snow") => return "weather-snow",
+        Some("thunderstorm") => return "weather-storm",
+        // "wind" and "clear-day" say nothing about precipitation, the condition does
+        _ => {}
+    }
+
+    match condition {
+        Some("fog") => "weather-fog",
+        Some("rain" | "sleet" | "hail") => "weather-showers",
+        Some("snow") => "weather-snow",
+        Some("thunderstorm") => "weather-storm",
+        _ => "weather-clear",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::mock::{self, MockServer};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/bright_sky.json");
+
+    fn records() -> Vec<Record> {
+        serde_json::from_str::<WeatherApi>(FIXTURE).unwrap().weather
+    }
+
+    #[test]
+    fn splits_days_at_midnight_in_the_requested_timezone() {
+        let days = daily(&records());
+
+        // Midnight in Berlin is 22:00 UTC on the day before
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2025, 10, 18).unwrap());
+        assert_eq!(days[0].min_temperature, 8.2);
+        assert_eq!(days[0].max_temperature, 9.6);
+        assert_eq!(days[0].precipitation, 0.3);
+        assert_eq!(days[0].precipitation_probability, Some(40.0));
+
+        assert_eq!(days[1].date, NaiveDate::from_ymd_opt(2025, 10, 19).unwrap());
+        assert_eq!(days[1].min_temperature, 7.9);
+        assert_eq!(days[1].precipitation, 0.8);
+        assert_eq!(days[1].max_wind_speed, 10.0);
+    }
+
+    #[test]
+    fn picks_the_most_common_icon_as_day_variant() {
+        let mut records = records();
+        records.truncate(1);
+
+        assert_eq!(daily(&records)[0].icon, "weather-few-clouds");
+    }
+
+    #[test]
+    fn converts_records_to_conditions_in_meters_per_second() {
+        let records = records();
+        let conditions = records[2].conditions().unwrap();
+
+        assert_eq!(conditions.time.to_rfc3339(), "2025-10-18T22:00:00+00:00");
+        assert_eq!(conditions.wind_speed, Some(10.0));
+        assert_eq!(conditions.icon, "weather-showers");
+        assert_eq!(conditions.precipitation_probability, Some(65.0));
+        assert!(records[3].conditions().is_none());
+    }
+
+    #[test]
+    fn maps_icons_and_falls_back_to_the_condition() {
+        let cases = [
+            (Some("clear-night"), None, "weather-clear-night"),
+            (
+                Some("partly-cloudy-night"),
+                None,
+                "weather-few-clouds-night",
+            ),
+            (Some("cloudy"), Some("rain"), "weather-overcast"),
+            (Some("sleet"), None, "weather-showers"),
+            (Some("thunderstorm"), None, "weather-storm"),
+            // Neither says anything about precipitation
+            (Some("clear-day"), Some("rain"), "weather-showers"),
+            (Some("wind"), Some("snow"), "weather-snow"),
+            (Some("clear-day"), Some("dry"), "weather-clear"),
+            (None, Some("fog"), "weather-fog"),
+            (None, None, "weather-clear"),
+        ];
+
+        for (icon, condition, expected) in cases {
+            assert_eq!(
+                condition_to_icon(icon, condition),
+                expected,
+                "{icon:?}, {condition:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn requests_records_in_the_timezone() {
+        let server = MockServer::respond("200 OK", &[], FIXTURE);
+        let provider = BrightSky::new(&server.url, String::new());
+
+        // The current weather request finds the server closed, which is tolerated
+        let response = mock::block_on(provider.forecast(52.52, 13.405)).unwrap();
+        let request = server.request();
+
+        assert!(
+            request.starts_with("GET /weather?lat=52.5200&lon=13.4050&date="),
+            "{request}"
+        );
+        assert!(request.contains("&tz=Europe/Berlin "), "{request}");
+        let forecast = response.forecast.unwrap();
+        assert!(forecast.current.is_none());
+        assert_eq!(forecast.hourly.len(), 3);
+    }
+}
@@ -0,0 +1,676 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Duration, Local, NaiveDate, Utc};
+use reqwest::{StatusCode, header};
+use serde::Deserialize;
+
+use super::{ForecastResponse, ProviderFuture, ProviderKind, WeatherProvider};
+use crate::{
+    error::FetchError,
+    http::{check_status, parse_http_date},
+    weather::{Attribution, Conditions, DailyForecast, Forecast},
+};
+
+pub const DEFAULT_URL: &str = "https://api.met.no/weatherapi";
+
+#[derive(Deserialize)]
+struct WeatherApi {
+    properties: Properties,
+}
+
+#[derive(Deserialize)]
+struct Properties {
+    timeseries: Vec<Timeseries>,
+}
+
+#[derive(Deserialize)]
+struct Timeseries {
+    time: DateTime<Utc>,
+    data: Data,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Data {
+    instant: Instant,
+    next_1_hours: Option<NextHours>,
+    next_6_hours: Option<NextHours>,
+    next_12_hours: Option<NextHours>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Instant {
+    details: InstantDetails,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct InstantDetails {
+    air_pressure_at_sea_level: f32,
+    air_temperature: f32,
+    cloud_area_fraction: f32,
+    relative_humidity: f32,
+    wind_from_direction: f32,
+    wind_speed: f32,
+    ultraviolet_index_clear_sky: f32,
+}
+
+/// Summary of the period following a timeseries entry
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct NextHours {
+    summary: Summary,
+    details: PeriodDetails,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Summary {
+    symbol_code: String,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct PeriodDetails {
+    precipitation_amount: f32,
+    precipitation_amount_min: Option<f32>,
+    precipitation_amount_max: Option<f32>,
+    probability_of_precipitation: Option<f32>,
+}
+
+impl Timeseries {
+    fn conditions(&self) -> Conditions {
+        let details = &self.data.instant.details;
+        let next = self.data.next_1_hours.as_ref();
+
+        Conditions {
+            time: self.time,
+            temperature: details.air_temperature,
+            apparent_temperature: None,
+            icon: next
+                .map_or("weather-clear", |next| {
+                    symbol_code_to_icon(&next.summary.symbol_code)
+                })
+                .to_string(),
+            uv_index: Some(details.ultraviolet_index_clear_sky),
+            pressure: Some(details.air_pressure_at_sea_level),
+            humidity: Some(details.relative_humidity),
+            cloud_cover: Some(details.cloud_area_fraction),
+            wind_speed: Some(details.wind_speed),
+            wind_direction: Some(details.wind_from_direction),
+            precipitation: next.map(|next| next.details.precipitation_amount),
+            precipitation_min: next.and_then(|next| next.details.precipitation_amount_min),
+            precipitation_max: next.and_then(|next| next.details.precipitation_amount_max),
+            precipitation_probability: next
+                .and_then(|next| next.details.probability_of_precipitation),
+        }
+    }
+
+    /// Symbol code of the longest period summary available for this entry
+    fn period_symbol_code(&self) -> Option<&str> {
+        [
+            &self.data.next_12_hours,
+            &self.data.next_6_hours,
+            &self.data.next_1_hours,
+        ]
+        .into_iter()
+        .flatten()
+        .map(|next| next.summary.symbol_code.as_str())
+        .find(|symbol_code| !symbol_code.is_empty())
+    }
+}
+
+impl WeatherApi {
+    fn into_forecast(self) -> Forecast {
+        let timeseries = self.properties.timeseries;
+
+        Forecast {
+            current: None,
+            // Only the first couple of days have hourly periods
+            hourly: timeseries
+                .iter()
+                .take_while(|ts| ts.data.next_1_hours.is_some())
+                .map(Timeseries::conditions)
+                .collect(),
+            daily: daily(&timeseries),
+            alerts: Vec::new(),
+            attribution: Attribution {
+                name: String::from("MET Norway"),
+                url: String::from("https://www.met.no/"),
+            },
+            station: None,
+        }
+    }
+}
+
+/// Aggregates the timeseries by local calendar day
+fn daily(timeseries: &[Timeseries]) -> Vec<DailyForecast> {
+    let mut by_date: BTreeMap<NaiveDate, Vec<&Timeseries>> = BTreeMap::new();
+
+    for ts in timeseries {
+        let date = ts.time.with_timezone(&Local).date_naive();
+        by_date.entry(date).or_default().push(ts);
+    }
+
+    by_date
+        .into_iter()
+        .map(|(date, entries)| aggregate_day(date, &entries))
+        .collect()
+}
+
+fn aggregate_day(date: NaiveDate, entries: &[&Timeseries]) -> DailyForecast {
+    let mut min_temperature = f32::MAX;
+    let mut max_temperature = f32::MIN;
+    let mut max_wind_speed = 0.0_f32;
+    let mut precipitation = 0.0;
+    let mut precipitation_probability: Option<f32> = None;
+    let mut covered_until: Option<DateTime<Utc>> = None;
+    let mut symbol_counts: BTreeMap<&str, usize> = BTreeMap::new();
+
+    for entry in entries {
+        let details = &entry.data.instant.details;
+        min_temperature = min_temperature.min(details.air_temperature);
+        max_temperature = max_temperature.max(details.air_temperature);
+        max_wind_speed = max_wind_speed.max(details.wind_speed);
+
+        // Sum the shortest periods available without counting any hour twice, since
+        // hourly and 6-hourly periods overlap for the first couple of days.
+        if covered_until.is_none_or(|until| entry.time >= until) {
+            if let Some(next) = &entry.data.next_1_hours {
+                precipitation += next.details.precipitation_amount;
+                covered_until = Some(entry.time + Duration::hours(1));
+            } else if let Some(next) = &entry.data.next_6_hours {
+                precipitation += next.details.precipitation_amount;
+                covered_until = Some(entry.time + Duration::hours(6));
+            }
+        }
+
+        let probability = [
+            &entry.data.next_1_hours,
+            &entry.data.next_6_hours,
+            &entry.data.next_12_hours,
+        ]
+        .into_iter()
+        .flatten()
+        .filter_map(|next| next.details.probability_of_precipitation)
+        .reduce(f32::max);
+        if let Some(probability) = probability {
+            precipitation_probability =
+                Some(precipitation_probability.map_or(probability, |p| p.max(probability)));
+        }
+
+        // Tally day and night variants together, the daily icon always uses the day variant.
+        if let Some(symbol_code) = entry.period_symbol_code() {
+            let (base, _) = split_symbol_code(symbol_code);
+            *symbol_counts.entry(base).or_default() += 1;
+        }
+    }
+
+    let dominant_symbol = symbol_counts
+        .into_iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(base, _)| base)
+        .unwrap_or_default();
+
+    DailyForecast {
+        date,
+        min_temperature,
+        max_temperature,
+        icon: symbol_code_to_icon(dominant_symbol).to_string(),
+        precipitation,
+        precipitation_probability,
+        max_wind_speed,
+    }
+}
+
+/// Last response for a location, along with its caching headers
+#[derive(Clone, Debug)]
+struct CachedResponse {
+    last_modified: Option<String>,
+    expires: Option<DateTime<Utc>>,
+    /// Forecast the response contained, which a not modified response confirms
+    forecast: Forecast,
+}
+
+/// Client for the met.no location forecast which honours its caching rules.
+///
+/// Clones share the responses remembered per location, so that requests made from
+/// different tasks stay conditional.
+#[derive(Clone)]
+pub struct MetNo {
+    client: reqwest::Client,
+    base_url: String,
+    user_agent: String,
+    responses: Arc<Mutex<HashMap<String, CachedResponse>>>,
+}
+
+impl WeatherProvider for MetNo {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::MetNo
+    }
+
+    fn forecast(&self, latitude: f64, longitude: f64) -> ProviderFuture<ForecastResponse> {
+        Box::pin(self.clone().get_location_forecast(latitude, longitude))
+    }
+}
+
+impl MetNo {
+    pub fn new(base_url: &str, user_agent: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            user_agent,
+            responses: Arc::default(),
+        }
+    }
+
+    async fn get_location_forecast(
+        self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<ForecastResponse, FetchError> {
+        // met.no asks for at most four decimals, which also keeps the cache key stable
+        let location = format!("lat={latitude:.4}&lon={longitude:.4}");
+        let url = format!("{}/locationforecast/2.0/complete?{location}", self.base_url);
+
+        let cached = self.cached_response(&location);
+
+        let mut request_builder = self
+            .client
+            .get(url)
+            .header(header::USER_AGENT, &self.user_agent);
+
+        // Only ask whether the forecast changed when it is at hand for this location
+        if let Some(last_modified) = cached
+            .as_ref()
+            .and_then(|cached| cached.last_modified.as_ref())
+        {
+            request_builder = request_builder.header(header::IF_MODIFIED_SINCE, last_modified);
+        }
+
+        let response = request_builder.send().await?;
+
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            let expires = parse_http_date(response.headers(), header::EXPIRES).or(cached.expires);
+            let forecast = cached.forecast.clone();
+            self.store_response(location, CachedResponse { expires, ..cached });
+
+            return Ok(ForecastResponse {
+                forecast: Some(forecast),
+                expires,
+            });
+        }
+
+        let response = check_status(response)?;
+        let last_modified = response
+            .headers()
+            .get(header::LAST_MODIFIED)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+        let expires = parse_http_date(response.headers(), header::EXPIRES);
+        let forecast = response.json::<WeatherApi>().await?.into_forecast();
+
+        self.store_response(
+            location,
+            CachedResponse {
+                last_modified,
+                expires,
+                forecast: forecast.clone(),
+            },
+        );
+
+        Ok(ForecastResponse {
+            forecast: Some(forecast),
+            expires,
+        })
+    }
+
+    fn cached_response(&self, location: &str) -> Option<CachedResponse> {
+        self.responses
+            .lock()
+            .ok()
+            .and_then(|responses| responses.get(location).cloned())
+    }
+
+    fn store_response(&self, location: String, response: CachedResponse) {
+        if let Ok(mut responses) = self.responses.lock() {
+            responses.insert(location, response);
+        }
+    }
+}
+
+/// Maps met.no/MET Norway symbol codes to freedesktop.org weather icon names
+fn symbol_code_to_icon(symbol_code: &str) -> &'static str {
+    let (base, is_night) = split_symbol_code(symbol_code);
+
+    match base {
+        // Clear sky
+        "clearsky" if is_night => "weather-clear-night",
+
+        // Partly cloudy / fair
+        "fair" | "partlycloudy" => {
+            if is_night {
+                "weather-few-clouds-night"
+            } else {
+                "weather-few-clouds"
+            }
+        }
+
+        // Overcast
+        "cloudy" => "weather-overcast",
+
+        // Fog
+        "fog" => "weather-fog",
+
+        // Rain (no thunder)
+        "lightrain" | "rain" | "heavyrain" => "weather-showers",
+
+        // Rain showers (no thunder)
+        "lightrainshowers" | "rainshowers" | "heavyrainshowers" => "weather-showers-scattered",
+
+        // Snow (all variants)
+        "lightsnow" | "snow" | "heavysnow" | "lightsnowshowers" | "snowshowers"
+        | "heavysnowshowers" => "weather-snow",
+
+        // Sleet (rain + snow mix)
+        "lightsleet" | "sleet" | "heavysleet" | "lightsleetshowers" | "sleetshowers"
+        | "heavysleetshowers" => "weather-showers",
+
+        // Thunder variants
+        "lightrainandthunder"
+        | "rainandthunder"
+        | "heavyrainandthunder"
+        | "lightrainshowersandthunder"
+        | "rainshowersandthunder"
+        | "heavyrainshowersandthunder"
+        | "lightsnowandthunder"
+        | "snowandthunder"
+        | "heavysnowandthunder"
+        | "lightssnowshowersandthunder"
+        | "snowshowersandthunder"
+        | "heavysnowshowersandthunder"
+        | "lightsleetandthunder"
+        | "sleetandthunder"
+        | "heavysleetandthunder"
+        | "lightssleetshowersandthunder"
+        | "sleetshowersandthunder"
+        | "heavysleetshowersandthunder" => "weather-storm",
+
+        // Fallback
+        _ => "weather-clear",
+    }
+}
+
+/// Parses out the time suffix (_day, _night, _polartwilight) of a symbol code
+fn split_symbol_code(symbol_code: &str) -> (&str, bool) {
+    if let Some(base) = symbol_code.strip_suffix("_night") {
+        (base, true)
+    } else if let Some(base) = symbol_code.strip_suffix("_polartwilight") {
+        (base, true)
+    } else if let Some(base) = symbol_code.strip_suffix("_day") {
+        (base, false)
+    } else {
+        (symbol_code, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::http::mock::{self, MockServer};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/met_no.json");
+
+    fn forecast() -> Forecast {
+        serde_json::from_str::<WeatherApi>(FIXTURE)
+            .unwrap()
+            .into_forecast()
+    }
+
+    fn period(symbol_code: &str, amount: f32, probability: Option<f32>) -> Option<NextHours> {
+        Some(NextHours {
+            summary: Summary {
+                symbol_code: symbol_code.to_string(),
+            },
+            details: PeriodDetails {
+                precipitation_amount: amount,
+                probability_of_precipitation: probability,
+                ..Default::default()
+            },
+        })
+    }
+
+    fn entry(hour: u32, temperature: f32, wind_speed: f32) -> Timeseries {
+        Timeseries {
+            time: Utc.with_ymd_and_hms(2026, 6, 1, hour, 0, 0).unwrap(),
+            data: Data {
+                instant: Instant {
+                    details: InstantDetails {
+                        air_temperature: temperature,
+                        wind_speed,
+                        ..Default::default()
+                    },
+                },
+                ..Default::default()
+            },
+        }
+    }
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 6, 1).unwrap()
+    }
+
+    #[test]
+    fn aggregates_temperature_and_wind() {
+        let entries = [
+            entry(0, 12.5, 3.0),
+            entry(6, 9.0, 7.5),
+            entry(12, 21.0, 4.0),
+        ];
+        let day = aggregate_day(date(), &entries.iter().collect::<Vec<_>>());
+
+        assert_eq!(day.date, date());
+        assert_eq!(day.min_temperature, 9.0);
+        assert_eq!(day.max_temperature, 21.0);
+        assert_eq!(day.max_wind_speed, 7.5);
+    }
+
+    #[test]
+    fn sums_precipitation_without_overlapping_periods() {
+        let mut entries: Vec<_> = (0..6).map(|hour| entry(hour, 10.0, 0.0)).collect();
+        for entry in &mut entries {
+            entry.data.next_1_hours = period("rain", 0.5, None);
+            entry.data.next_6_hours = period("rain", 3.0, None);
+        }
+
+        // Past the hourly periods only 6-hourly ones are left
+        let mut later = entry(6, 10.0, 0.0);
+        later.data.next_6_hours = period("rain", 2.0, None);
+        entries.push(later);
+        let mut overlapping = entry(9, 10.0, 0.0);
+        overlapping.data.next_6_hours = period("rain", 4.0, None);
+        entries.push(overlapping);
+
+        let day = aggregate_day(date(), &entries.iter().collect::<Vec<_>>());
+
+        assert_eq!(day.precipitation, 5.0);
+    }
+
+    #[test]
+    fn takes_the_highest_precipitation_probability() {
+        let mut first = entry(0, 10.0, 0.0);
+        first.data.next_1_hours = period("cloudy", 0.0, Some(10.0));
+        first.data.next_6_hours = period("rain", 1.0, Some(40.0));
+        let mut second = entry(6, 10.0, 0.0);
+        second.data.next_6_hours = period("rain", 1.0, Some(25.0));
+
+        let day = aggregate_day(date(), &[&first, &second]);
+        assert_eq!(day.precipitation_probability, Some(40.0));
+
+        let dry = aggregate_day(date(), &[&entry(0, 10.0, 0.0)]);
+        assert_eq!(dry.precipitation_probability, None);
+    }
+
+    #[test]
+    fn picks_the_most_frequent_symbol_of_either_variant() {
+        let symbols = ["partlycloudy_night", "rain", "partlycloudy_day"];
+        let entries: Vec<_> = symbols
+            .iter()
+            .zip(0..)
+            .map(|(symbol, hour)| {
+                let mut entry = entry(hour * 6, 10.0, 0.0);
+                entry.data.next_6_hours = period(symbol, 0.0, None);
+                entry
+            })
+            .collect();
+
+        let day = aggregate_day(date(), &entries.iter().collect::<Vec<_>>());
+
+        assert_eq!(day.icon, "weather-few-clouds");
+    }
+
+    #[test]
+    fn keeps_the_hours_of_the_timeseries_with_hourly_periods() {
+        let forecast = forecast();
+
+        let hours: Vec<_> = forecast
+            .hourly
+            .iter()
+            .map(|hour| (hour.time.timestamp(), hour.temperature, hour.icon.as_str()))
+            .collect();
+        assert_eq!(
+            hours,
+            [
+                (1780315200, 17.3, "weather-showers"),
+                (1780318800, 18.1, "weather-few-clouds"),
+                (1780354800, 11.6, "weather-clear-night"),
+            ]
+        );
+        assert_eq!(forecast.attribution.name, "MET Norway");
+    }
+
+    #[test]
+    fn keeps_the_instant_details() {
+        let hour = &forecast().hourly[0];
+
+        assert_eq!(hour.pressure, Some(1008.4));
+        assert_eq!(hour.humidity, Some(67.5));
+        assert_eq!(hour.cloud_cover, Some(92.2));
+        assert_eq!(hour.uv_index, Some(5.1));
+        assert_eq!(hour.wind_speed, Some(4.2));
+        assert_eq!(hour.wind_direction, Some(214.6));
+        // met.no does not forecast a felt temperature
+        assert_eq!(hour.apparent_temperature, None);
+    }
+
+    #[test]
+    fn parses_precipitation_ranges_and_probabilities() {
+        let hourly = forecast().hourly;
+
+        assert_eq!(hourly[0].precipitation, Some(0.6));
+        assert_eq!(hourly[0].precipitation_min, Some(0.2));
+        assert_eq!(hourly[0].precipitation_max, Some(1.1));
+        assert_eq!(hourly[0].precipitation_probability, Some(65.3));
+        // Not every period reports a range
+        assert_eq!(hourly[2].precipitation, Some(0.0));
+        assert_eq!(hourly[2].precipitation_max, None);
+    }
+
+    #[test]
+    fn maps_symbol_codes_to_icons() {
+        let cases = [
+            ("clearsky_day", "weather-clear"),
+            ("clearsky_polartwilight", "weather-clear-night"),
+            ("fair_night", "weather-few-clouds-night"),
+            ("cloudy", "weather-overcast"),
+            ("fog", "weather-fog"),
+            ("heavyrainshowers_day", "weather-showers-scattered"),
+            ("lightsleet", "weather-showers"),
+            ("snowshowers_night", "weather-snow"),
+            ("lightssleetshowersandthunder_day", "weather-storm"),
+            ("unknown", "weather-clear"),
+        ];
+
+        for (symbol_code, icon) in cases {
+            assert_eq!(symbol_code_to_icon(symbol_code), icon, "{symbol_code}");
+        }
+    }
+
+    #[test]
+    fn asks_whether_the_forecast_of_a_location_changed() {
+        let last_modified = "Mon, 01 Jun 2026 11:42:19 GMT";
+        let server = MockServer::respond(
+            "200 OK",
+            &[
+                ("Last-Modified", last_modified),
+                ("Expires", "Mon, 01 Jun 2026 12:10:00 GMT"),
+            ],
+            FIXTURE,
+        );
+        let provider = MetNo::new(&server.url, String::from("test-agent"));
+
+        let response = mock::block_on(provider.forecast(59.9139, 10.7522)).unwrap();
+        let request = server.request();
+
+        assert!(
+            request.starts_with("GET /locationforecast/2.0/complete?lat=59.9139&lon=10.7522 "),
+            "{request}"
+        );
+        assert!(!request.to_lowercase().contains("if-modified-since"));
+        assert_eq!(
+            response.expires,
+            Utc.with_ymd_and_hms(2026, 6, 1, 12, 10, 0).single()
+        );
+
+        // Clones share the remembered responses
+        let server = MockServer::respond(
+            "304 Not Modified",
+            &[("Expires", "Mon, 01 Jun 2026 12:40:00 GMT")],
+            "",
+        );
+        let provider = MetNo {
+            base_url: server.url.clone(),
+            ..provider.clone()
+        };
+
+        let response = mock::block_on(provider.forecast(59.9139, 10.7522)).unwrap();
+        let request = server.request().to_lowercase();
+
+        assert!(
+            request.contains(&format!(
+                "if-modified-since: {}",
+                last_modified.to_lowercase()
+            )),
+            "{request}"
+        );
+        assert_eq!(response.forecast.unwrap().hourly.len(), 3);
+        assert_eq!(
+            response.expires,
+            Utc.with_ymd_and_hms(2026, 6, 1, 12, 40, 0).single()
+        );
+
+        // Nothing is remembered for another location yet
+        let server = MockServer::respond("200 OK", &[], FIXTURE);
+        let provider = MetNo {
+            base_url: server.url.clone(),
+            ..provider
+        };
+
+        mock::block_on(provider.forecast(60.3913, 5.3221)).unwrap();
+
+        assert!(
+            !server
+                .request()
+                .to_lowercase()
+                .contains("if-modified-since")
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::FetchError,
+    geocoding, http,
+    location::ip::{self, IpService},
+    weather::Forecast,
+};
+
+pub mod bright_sky;
+pub mod met_no;
+pub mod nws;
+pub mod open_meteo;
+pub mod open_weather_map;
+
+/// Future returned by providers, boxed so that providers can be used as trait objects
+pub type ProviderFuture<T> = Pin<Box<dyn Future<Output = Result<T, FetchError>> + Send>>;
+
+#[derive(Clone, Debug)]
+pub struct ForecastResponse {
+    /// `None` when the forecast has not changed since the previous request
+    pub forecast: Option<Forecast>,
+    /// Time after which the provider may have a newer forecast for the location
+    pub expires: Option<DateTime<Utc>>,
+}
+
+/// Source of forecasts, converting its API's data into the shared [`Forecast`] model.
+///
+/// Implementations are cheap to clone into the returned future, so that the
+/// request can outlive the borrow of the provider.
+pub trait WeatherProvider: Send + Sync {
+    fn kind(&self) -> ProviderKind;
+
+    fn forecast(&self, latitude: f64, longitude: f64) -> ProviderFuture<ForecastResponse>;
+}
+
+/// Weather providers that can be chosen in the settings
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProviderKind {
+    #[default]
+    MetNo,
+    OpenMeteo,
+    Nws,
+    BrightSky,
+    OpenWeatherMap,
+}
+
+impl ProviderKind {
+    pub const ALL: [Self; 5] = [
+        Self::MetNo,
+        Self::OpenMeteo,
+        Self::Nws,
+        Self::BrightSky,
+        Self::OpenWeatherMap,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::MetNo => "met.no",
+            Self::OpenMeteo => "Open-Meteo",
+            Self::Nws => "NWS",
+            Self::BrightSky => "DWD",
+            Self::OpenWeatherMap => "OWM",
+        }
+    }
+
+    pub fn create(self, settings: &ProviderSettings) -> Arc<dyn WeatherProvider> {
+        let user_agent = settings.user_agent();
+
+        match self {
+            Self::MetNo => Arc::new(met_no::MetNo::new(&settings.met_no_url, user_agent)),
+            Self::OpenMeteo => Arc::new(open_meteo::OpenMeteo::new(
+                &settings.open_meteo_url,
+                user_agent,
+            )),
+            Self::Nws => Arc::new(nws::Nws::new(&settings.nws_url, user_agent)),
+            Self::BrightSky => Arc::new(bright_sky::BrightSky::new(
+                &settings.bright_sky_url,
+                user_agent,
+            )),
+            Self::OpenWeatherMap => Arc::new(open_weather_map::OpenWeatherMap::new(
+                &settings.open_weather_map_url,
+                &settings.open_weather_map_api_key,
+                user_agent,
+            )),
+        }
+    }
+}
+
+/// Remote services whose base URL can be overridden, for mirrors, caching
+/// proxies and local test servers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endpoint {
+    Provider(ProviderKind),
+    IpService(IpService),
+    Geocoding,
+    ReverseGeocoding,
+}
+
+impl Endpoint {
+    pub fn all() -> impl Iterator<Item = Self> {
+        ProviderKind::ALL
+            .into_iter()
+            .map(Self::Provider)
+            .chain(IpService::ALL.into_iter().map(Self::IpService))
+            .chain([Self::Geocoding, Self::ReverseGeocoding])
+    }
+
+    pub fn url(self, settings: &ProviderSettings) -> &str {
+        match self {
+            Self::Provider(ProviderKind::MetNo) => &settings.met_no_url,
+            Self::Provider(ProviderKind::OpenMeteo) => &settings.open_meteo_url,
+            Self::Provider(ProviderKind::Nws) => &settings.nws_url,
+            Self::Provider(ProviderKind::BrightSky) => &settings.bright_sky_url,
+            Self::Provider(ProviderKind::OpenWeatherMap) => &settings.open_weather_map_url,
+            Self::IpService(IpService::IpInfo) => &settings.ipinfo_url,
+            Self::IpService(IpService::IpApiCo) => &settings.ipapi_co_url,
+            Self::IpService(IpService::IfConfig) => &settings.ifconfig_url,
+            Self::IpService(IpService::SelfHosted) => &settings.self_hosted_ip_url,
+            Self::Geocoding => &settings.geocoding_url,
+            Self::ReverseGeocoding => &settings.reverse_geocoding_url,
+        }
+    }
+
+    pub fn set_url(self, settings: &mut ProviderSettings, url: String) {
+        let field = match self {
+            Self::Provider(ProviderKind::MetNo) => &mut settings.met_no_url,
+            Self::Provider(ProviderKind::OpenMeteo) => &mut settings.open_meteo_url,
+            Self::Provider(ProviderKind::Nws) => &mut settings.nws_url,
+            Self::Provider(ProviderKind::BrightSky) => &mut settings.bright_sky_url,
+            Self::Provider(ProviderKind::OpenWeatherMap) => &mut settings.open_weather_map_url,
+            Self::IpService(IpService::IpInfo) => &mut settings.ipinfo_url,
+            Self::IpService(IpService::IpApiCo) => &mut settings.ipapi_co_url,
+            Self::IpService(IpService::IfConfig) => &mut settings.ifconfig_url,
+            Self::IpService(IpService::SelfHosted) => &mut settings.self_hosted_ip_url,
+            Self::Geocoding => &mut settings.geocoding_url,
+            Self::ReverseGeocoding => &mut settings.reverse_geocoding_url,
+        };
+        *field = url;
+    }
+}
+
+/// Endpoints and credentials of the weather and location services, stored in the applet config
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderSettings {
+    /// Contact address appended to the User-Agent, which met.no's terms of service ask for
+    pub contact: String,
+    /// Base URL of the met.no weather API, without the `/locationforecast` path
+    pub met_no_url: String,
+    /// Base URL of the Open-Meteo API, without the `/v1/forecast` path
+    pub open_meteo_url: String,
+    /// Base URL of the US National Weather Service API
+    pub nws_url: String,
+    /// Base URL of the Bright Sky API serving DWD data
+    pub bright_sky_url: String,
+    /// Base URL of the OpenWeatherMap API
+    pub open_weather_map_url: String,
+    /// Key for the OpenWeatherMap One Call API
+    pub open_weather_map_api_key: String,
+    /// URL of ipinfo.io's IP geolocation lookup
+    pub ipinfo_url: String,
+    /// URL of ipapi.co's IP geolocation lookup
+    pub ipapi_co_url: String,
+    /// URL of ifconfig.co's IP geolocation lookup
+    pub ifconfig_url: String,
+    /// URL of a self-hosted IP geolocation service answering like ifconfig.co or ip-api.com
+    pub self_hosted_ip_url: String,
+    /// Base URL of the Open-Meteo compatible geocoding API used by the location search
+    pub geocoding_url: String,
+    /// Base URL of the Nominatim compatible API naming coordinates entered by hand
+    pub reverse_geocoding_url: String,
+}
+
+impl ProviderSettings {
+    pub fn user_agent(&self) -> String {
+        http::user_agent(&self.contact)
+    }
+}
+
+impl Default for ProviderSettings {
+    fn default() -> Self {
+        Self {
+            contact: String::new(),
+            met_no_url: String::from(met_no::DEFAULT_URL),
+            open_meteo_url: String::from(open_meteo::DEFAULT_URL),
+            nws_url: String::from(nws::DEFAULT_URL),
+            bright_sky_url: String::from(bright_sky::DEFAULT_URL),
+            open_weather_map_url: String::from(open_weather_map::DEFAULT_URL),
+            open_weather_map_api_key: String::new(),
+            ipinfo_url: String::from(ip::DEFAULT_IPINFO_URL),
+            ipapi_co_url: String::from(ip::DEFAULT_IPAPI_CO_URL),
+            ifconfig_url: String::from(ip::DEFAULT_IFCONFIG_URL),
+            self_hosted_ip_url: String::from(ip::DEFAULT_SELF_HOSTED_URL),
+            geocoding_url: String::from(geocoding::DEFAULT_URL),
+            reverse_geocoding_url: String::from(geocoding::DEFAULT_REVERSE_URL),
+        }
+    }
+}
@@ -0,0 +1,603 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+use reqwest::header;
+use serde::{Deserialize, de::DeserializeOwned};
+
+use super::{ForecastResponse, ProviderFuture, ProviderKind, WeatherProvider};
+use crate::{
+    error::FetchError,
+    http::{check_status, parse_http_date},
+    weather::{Alert, AlertSeverity, Attribution, Conditions, DailyForecast, Forecast},
+};
+
+pub const DEFAULT_URL: &str = "https://api.weather.gov";
+
+/// Refresh interval used when a response has no `Expires` header
+const REFRESH_INTERVAL: Duration = Duration::minutes(30);
+
+#[derive(Deserialize)]
+struct PointsApi {
+    properties: PointProperties,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PointProperties {
+    forecast: String,
+    forecast_hourly: String,
+}
+
+#[derive(Deserialize)]
+struct ForecastApi {
+    properties: ForecastProperties,
+}
+
+#[derive(Deserialize)]
+struct ForecastProperties {
+    periods: Vec<Period>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Period {
+    start_time: DateTime<FixedOffset>,
+    is_daytime: bool,
+    temperature: f32,
+    #[serde(default)]
+    probability_of_precipitation: Value,
+    #[serde(default)]
+    relative_humidity: Value,
+    #[serde(default)]
+    wind_speed: String,
+    #[serde(default)]
+    wind_direction: String,
+    #[serde(default)]
+    icon: String,
+    #[serde(default)]
+    short_forecast: String,
+}
+
+/// Quantity with a unit code, which is `null` when not forecast
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Value {
+    value: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct AlertsApi {
+    features: Vec<AlertFeature>,
+}
+
+#[derive(Deserialize)]
+struct AlertFeature {
+    properties: AlertProperties,
+}
+
+#[derive(Deserialize)]
+struct AlertProperties {
+    event: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    severity: String,
+    onset: Option<DateTime<Utc>>,
+    ends: Option<DateTime<Utc>>,
+    expires: Option<DateTime<Utc>>,
+}
+
+impl Period {
+    fn icon(&self) -> &'static str {
+        icon_url_to_icon(&self.icon, self.is_daytime)
+            .unwrap_or_else(|| short_forecast_to_icon(&self.short_forecast, self.is_daytime))
+    }
+
+    fn conditions(&self) -> Conditions {
+        Conditions {
+            time: self.start_time.with_timezone(&Utc),
+            temperature: self.temperature,
+            apparent_temperature: None,
+            icon: self.icon().to_string(),
+            uv_index: None,
+            pressure: None,
+            humidity: self.relative_humidity.value,
+            cloud_cover: None,
+            wind_speed: parse_wind_speed(&self.wind_speed),
+            wind_direction: compass_to_degrees(&self.wind_direction),
+            precipitation: None,
+            precipitation_probability: self.probability_of_precipitation.value,
+            precipitation_min: None,
+            precipitation_max: None,
+        }
+    }
+}
+
+impl From<AlertProperties> for Alert {
+    fn from(alert: AlertProperties) -> Self {
+        Alert {
+            title: alert.event,
+            description: alert.description,
+            severity: match alert.severity.as_str() {
+                "Minor" => AlertSeverity::Minor,
+                "Moderate" => AlertSeverity::Moderate,
+                "Severe" => AlertSeverity::Severe,
+                "Extreme" => AlertSeverity::Extreme,
+                _ => AlertSeverity::Unknown,
+            },
+            start: alert.onset,
+            end: alert.ends.or(alert.expires),
+        }
+    }
+}
+
+/// Combines the day and night periods of each local date into a daily forecast
+fn daily(periods: &[Period]) -> Vec<DailyForecast> {
+    let mut by_date: BTreeMap<NaiveDate, (Option<&Period>, Option<&Period>)> = BTreeMap::new();
+
+    for period in periods {
+        let (day, night) = by_date.entry(period.start_time.date_naive()).or_default();
+        if period.is_daytime {
+            *day = Some(period);
+        } else {
+            *night = Some(period);
+        }
+    }
+
+    by_date
+        .into_iter()
+        .filter_map(|(date, (day, night))| {
+            // The first period may be tonight only, which then gives both temperatures
+            let main = day.or(night)?;
+            let max_temperature = day.unwrap_or(main).temperature;
+            let min_temperature = night.unwrap_or(main).temperature;
+            let periods = [day, night].into_iter().flatten();
+
+            Some(DailyForecast {
+                date,
+                min_temperature: min_temperature.min(max_temperature),
+                max_temperature: max_temperature.max(min_temperature),
+                icon: icon_url_to_icon(&main.icon, true)
+                    .unwrap_or_else(|| short_forecast_to_icon(&main.short_forecast, true))
+                    .to_string(),
+                // Period forecasts only give a probability, not an amount
+                precipitation: 0.0,
+                precipitation_probability: periods
+                    .clone()
+                    .filter_map(|period| period.probability_of_precipitation.value)
+                    .reduce(f32::max),
+                max_wind_speed: periods
+                    .filter_map(|period| parse_wind_speed(&period.wind_speed))
+                    .fold(0.0, f32::max),
+            })
+        })
+        .collect()
+}
+
+/// Forecast URLs of the grid cell covering a location
+#[derive(Clone, Debug)]
+struct Gridpoint {
+    forecast: String,
+    forecast_hourly: String,
+}
+
+/// Client for the US National Weather Service API.
+///
+/// The gridpoint a location resolves to rarely changes, so it is looked up once
+/// per location and shared between clones.
+#[derive(Clone)]
+pub struct Nws {
+    client: reqwest::Client,
+    base_url: String,
+    user_agent: String,
+    gridpoints: Arc<Mutex<HashMap<String, Gridpoint>>>,
+}
+
+impl Nws {
+    pub fn new(base_url: &str, user_agent: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            user_agent,
+            gridpoints: Arc::default(),
+        }
+    }
+
+    async fn get<T: DeserializeOwned>(
+        &self,
+        url: &str,
+    ) -> Result<(T, Option<DateTime<Utc>>), FetchError> {
+        let request_builder = self
+            .client
+            .get(url)
+            .header(header::USER_AGENT, &self.user_agent)
+            .header(header::ACCEPT, "application/geo+json");
+
+        let response = check_status(request_builder.send().await?)?;
+        let expires = parse_http_date(response.headers(), header::EXPIRES);
+
+        Ok((response.json::<T>().await?, expires))
+    }
+
+    async fn gridpoint(&self, location: &str) -> Result<Gridpoint, FetchError> {
+        let cached = self
+            .gridpoints
+            .lock()
+            .ok()
+            .and_then(|gridpoints| gridpoints.get(location).cloned());
+        if let Some(gridpoint) = cached {
+            return Ok(gridpoint);
+        }
+
+        let url = format!("{}/points/{location}", self.base_url);
+        let (points, _) = self
+            .get::<PointsApi>(&url)
+            .await
+            // The NWS only covers the United States and its territories
+            .map_err(|e| match e {
+                FetchError::HttpStatus(404) => FetchError::UnsupportedLocation,
+                e => e,
+            })?;
+
+        let gridpoint = Gridpoint {
+            forecast: points.properties.forecast,
+            forecast_hourly: points.properties.forecast_hourly,
+        };
+
+        if let Ok(mut gridpoints) = self.gridpoints.lock() {
+            gridpoints.insert(location.to_string(), gridpoint.clone());
+        }
+
+        Ok(gridpoint)
+    }
+
+    async fn get_forecast(
+        self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<ForecastResponse, FetchError> {
+        // The API redirects requests with more than four decimals
+        let location = format!("{latitude:.4},{longitude:.4}");
+        let gridpoint = self.gridpoint(&location).await?;
+
+        let (hourly, expires) = self
+            .get::<ForecastApi>(&format!("{}?units=si", gridpoint.forecast_hourly))
+            .await?;
+        let (periods, _) = self
+            .get::<ForecastApi>(&format!("{}?units=si", gridpoint.forecast))
+            .await?;
+
+        // Alerts are a bonus, a failure to fetch them should not hide the forecast
+        let alerts = match self
+            .get::<AlertsApi>(&format!("{}/alerts/active?point={location}", self.base_url))
+            .await
+        {
+            Ok((alerts, _)) => alerts
+                .features
+                .into_iter()
+                .map(|feature| feature.properties.into())
+                .collect(),
+            Err(e) => {
+                tracing::warn!("Failed to get weather alerts: {e}");
+                Vec::new()
+            }
+        };
+
+        let forecast = Forecast {
+            current: None,
+            hourly: hourly
+                .properties
+                .periods
+                .iter()
+                .map(Period::conditions)
+                .collect(),
+            daily: daily(&periods.properties.periods),
+            alerts,
+            attribution: Attribution {
+                name: String::from("National Weather Service"),
+                url: String::from("https://www.weather.gov/"),
+            },
+            station: None,
+        };
+
+        Ok(ForecastResponse {
+            forecast: Some(forecast),
+            expires: expires.or_else(|| Some(Utc::now() + REFRESH_INTERVAL)),
+        })
+    }
+}
+
+impl WeatherProvider for Nws {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Nws
+    }
+
+    fn forecast(&self, latitude: f64, longitude: f64) -> ProviderFuture<ForecastResponse> {
+        Box::pin(self.clone().get_forecast(latitude, longitude))
+    }
+}
+
+/// Parses wind speeds such as "10 km/h", "5 to 15 km/h" or "Calm" into m/s, taking the
+/// upper bound
+fn parse_wind_speed(wind_speed: &str) -> Option<f32> {
+    if wind_speed.eq_ignore_ascii_case("calm") {
+        return Some(0.0);
+    }
+
+    let speed = wind_speed
+        .split_whitespace()
+        .filter_map(|word| word.parse::<f32>().ok())
+        .reduce(f32::max)?;
+
+    if wind_speed.ends_with("mph") {
+        Some(speed / 2.236_936)
+    } else {
+        Some(speed / 3.6)
+    }
+}
+
+/// Converts a 16-point compass direction such as "NNW" into degrees
+fn compass_to_degrees(direction: &str) -> Option<f32> {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+
+    POINTS
+        .iter()
+        .position(|point| *point == direction)
+        .map(|index| index as f32 * 22.5)
+}
+
+/// Maps NWS icon URLs such as `.../icons/land/night/tsra_hi,40/sct?size=small`
+/// to freedesktop.org weather icon names, using the first condition of the URL
+fn icon_url_to_icon(url: &str, is_day: bool) -> Option<&'static str> {
+    let path = url.split('?').next()?;
+    let (_, conditions) = path.split_once("/day/").or(path.split_once("/night/"))?;
+    let condition = conditions.split(['/', ',']).next()?;
+
+    let icon = match condition {
+        // Clear sky
+        "skc" | "wind_skc" | "hot" | "cold" => {
+            if is_day {
+                "weather-clear"
+            } else {
+                "weather-clear-night"
+            }
+        }
+
+        // Few or scattered clouds
+        "few" | "sct" | "wind_few" | "wind_sct" => {
+            if is_day {
+                "weather-few-clouds"
+            } else {
+                "weather-few-clouds-night"
+            }
+        }
+
+        // Mostly cloudy / overcast
+        "bkn" | "ovc" | "wind_bkn" | "wind_ovc" => "weather-overcast",
+
+        // Fog and other obscurations
+        "fog" | "haze" | "smoke" | "dust" => "weather-fog",
+
+        // Rain, sleet and freezing rain
+        "rain" | "rain_sleet" | "rain_snow" | "rain_fzra" | "fzra" | "sleet" => "weather-showers",
+
+        // Rain showers
+        "rain_showers" | "rain_showers_hi" => "weather-showers-scattered",
+
+        // Snow
+        "snow" | "snow_sleet" | "snow_fzra" | "blizzard" => "weather-snow",
+
+        // Thunderstorms and tropical systems
+        "tsra" | "tsra_sct" | "tsra_hi" | "tornado" | "hurricane" | "tropical_storm" => {
+            "weather-storm"
+        }
+
+        _ => return None,
+    };
+
+    Some(icon)
+}
+
+/// Picks an icon from a short forecast such as "Chance Rain Showers", for
+/// periods whose icon URL is missing or unknown
+fn short_forecast_to_icon(short_forecast: &str, is_day: bool) -> &'static str {
+    let text = short_forecast.to_lowercase();
+
+    if text.contains("thunder") {
+        "weather-storm"
+    } else if text.contains("snow") || text.contains("flurries") || text.contains("blizzard") {
+        "weather-snow"
+    } else if text.contains("showers") {
+        "weather-showers-scattered"
+    } else if text.contains("rain") || text.contains("drizzle") || text.contains("sleet") {
+        "weather-showers"
+    } else if text.contains("fog") || text.contains("haze") || text.contains("smoke") {
+        "weather-fog"
+    } else if text.contains("cloudy") {
+        if text.contains("partly") {
+            if is_day {
+                "weather-few-clouds"
+            } else {
+                "weather-few-clouds-night"
+            }
+        } else {
+            "weather-overcast"
+        }
+    } else if text.contains("partly")
+        || text.contains("mostly sunny")
+        || text.contains("mostly clear")
+    {
+        if is_day {
+            "weather-few-clouds"
+        } else {
+            "weather-few-clouds-night"
+        }
+    } else if is_day {
+        "weather-clear"
+    } else {
+        "weather-clear-night"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/nws_forecast.json");
+
+    fn periods() -> Vec<Period> {
+        serde_json::from_str::<ForecastApi>(FIXTURE)
+            .unwrap()
+            .properties
+            .periods
+    }
+
+    fn assert_speed(wind_speed: &str, expected: Option<f32>) {
+        let speed = parse_wind_speed(wind_speed);
+        assert!(
+            match (speed, expected) {
+                (Some(speed), Some(expected)) => (speed - expected).abs() < 0.01,
+                (speed, expected) => speed == expected,
+            },
+            "{wind_speed}: {speed:?}"
+        );
+    }
+
+    #[test]
+    fn parses_wind_speeds_and_ranges() {
+        assert_speed("18 km/h", Some(5.0));
+        assert_speed("10 to 36 km/h", Some(10.0));
+        assert_speed("10 mph", Some(4.47));
+        assert_speed("10 to 15 mph", Some(6.71));
+        assert_speed("Calm", Some(0.0));
+        assert_speed("", None);
+        assert_speed("Variable", None);
+    }
+
+    #[test]
+    fn converts_compass_points_to_degrees() {
+        assert_eq!(compass_to_degrees("N"), Some(0.0));
+        assert_eq!(compass_to_degrees("ENE"), Some(67.5));
+        assert_eq!(compass_to_degrees("SSW"), Some(202.5));
+        assert_eq!(compass_to_degrees("NNW"), Some(337.5));
+        assert_eq!(compass_to_degrees(""), None);
+        assert_eq!(compass_to_degrees("nw"), None);
+    }
+
+    #[test]
+    fn maps_icon_urls_by_their_first_condition() {
+        let icon = |url| icon_url_to_icon(url, url.contains("/day/"));
+
+        assert_eq!(
+            icon("https://api.weather.gov/icons/land/day/tsra_hi,40/sct?size=medium"),
+            Some("weather-storm")
+        );
+        assert_eq!(
+            icon("https://api.weather.gov/icons/land/night/few?size=small"),
+            Some("weather-few-clouds-night")
+        );
+        assert_eq!(
+            icon("https://api.weather.gov/icons/land/night/skc?size=small"),
+            Some("weather-clear-night")
+        );
+        assert_eq!(
+            icon("https://api.weather.gov/icons/land/day/rain_showers,60/rain_showers,30"),
+            Some("weather-showers-scattered")
+        );
+        assert_eq!(
+            icon("https://api.weather.gov/icons/land/day/bkn/fog?size=medium"),
+            Some("weather-overcast")
+        );
+        assert_eq!(
+            icon("https://api.weather.gov/icons/land/day/new_condition?size=medium"),
+            None
+        );
+        assert_eq!(icon("https://example.com/sunny.png"), None);
+        assert_eq!(icon(""), None);
+    }
+
+    #[test]
+    fn maps_short_forecasts() {
+        let cases = [
+            ("Chance Showers And Thunderstorms", true, "weather-storm"),
+            ("Light Snow Likely", true, "weather-snow"),
+            (
+                "Slight Chance Rain Showers",
+                true,
+                "weather-showers-scattered",
+            ),
+            ("Areas Of Drizzle", false, "weather-showers"),
+            ("Patchy Fog then Sunny", true, "weather-fog"),
+            ("Partly Cloudy", false, "weather-few-clouds-night"),
+            ("Mostly Cloudy", true, "weather-overcast"),
+            ("Mostly Sunny", true, "weather-few-clouds"),
+            ("Sunny", true, "weather-clear"),
+            ("Clear", false, "weather-clear-night"),
+        ];
+
+        for (short_forecast, is_day, icon) in cases {
+            assert_eq!(
+                short_forecast_to_icon(short_forecast, is_day),
+                icon,
+                "{short_forecast}"
+            );
+        }
+    }
+
+    #[test]
+    fn falls_back_to_the_short_forecast_for_unknown_icons() {
+        let periods = periods();
+
+        assert_eq!(periods[1].icon(), "weather-storm");
+        assert_eq!(periods[3].icon(), "weather-fog");
+    }
+
+    #[test]
+    fn pairs_day_and_night_periods_by_date() {
+        let days = daily(&periods());
+        assert_eq!(days.len(), 3);
+
+        // The forecast starts with tonight, which gives both temperatures
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2025, 10, 18).unwrap());
+        assert_eq!(
+            (days[0].min_temperature, days[0].max_temperature),
+            (12.0, 12.0)
+        );
+        assert_eq!(days[0].icon, "weather-few-clouds");
+        assert_eq!(days[0].max_wind_speed, 0.0);
+
+        // Night periods belong to the date they start on
+        assert_eq!(days[1].date, NaiveDate::from_ymd_opt(2025, 10, 19).unwrap());
+        assert_eq!(
+            (days[1].min_temperature, days[1].max_temperature),
+            (10.0, 21.0)
+        );
+        assert_eq!(days[1].icon, "weather-storm");
+        assert_eq!(days[1].precipitation_probability, Some(60.0));
+        assert_eq!(days[1].max_wind_speed, 10.0);
+
+        assert_eq!(
+            (days[2].min_temperature, days[2].max_temperature),
+            (18.0, 18.0)
+        );
+        assert_eq!(days[2].precipitation_probability, None);
+    }
+
+    #[test]
+    fn converts_periods_to_conditions() {
+        let conditions = periods()[2].conditions();
+
+        assert_eq!(conditions.time.to_rfc3339(), "2025-10-19T22:00:00+00:00");
+        assert_eq!(conditions.temperature, 10.0);
+        assert_eq!(conditions.icon, "weather-showers-scattered");
+        assert_eq!(conditions.wind_speed, Some(10.0));
+        assert_eq!(conditions.wind_direction, Some(292.5));
+        assert_eq!(conditions.precipitation_probability, Some(60.0));
+    }
+}
@@ -0,0 +1,430 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header;
+use serde::Deserialize;
+
+use super::{ForecastResponse, ProviderFuture, ProviderKind, WeatherProvider};
+use crate::{
+    error::FetchError,
+    http::check_status,
+    weather::{Attribution, Conditions, DailyForecast, Forecast},
+};
+
+pub const DEFAULT_URL: &str = "https://api.open-meteo.com";
+
+/// Number of days requested, matching what the popup shows
+const FORECAST_DAYS: u8 = 9;
+
+/// Open-Meteo updates its current conditions every 15 minutes and sends no caching headers
+const REFRESH_INTERVAL: Duration = Duration::minutes(15);
+
+const CURRENT_VARIABLES: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,\
+    weather_code,is_day,cloud_cover,pressure_msl,wind_speed_10m,wind_direction_10m,uv_index";
+
+const HOURLY_VARIABLES: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,\
+    precipitation_probability,precipitation,weather_code,is_day,cloud_cover,pressure_msl,\
+    wind_speed_10m,wind_direction_10m,uv_index";
+
+const DAILY_VARIABLES: &str = "weather_code,temperature_2m_max,temperature_2m_min,\
+    precipitation_sum,precipitation_probability_max,wind_speed_10m_max";
+
+#[derive(Deserialize)]
+struct ForecastApi {
+    utc_offset_seconds: i64,
+    current: Option<Current>,
+    #[serde(default)]
+    hourly: Hourly,
+    #[serde(default)]
+    daily: Daily,
+}
+
+#[derive(Deserialize)]
+struct Current {
+    time: i64,
+    temperature_2m: f32,
+    apparent_temperature: Option<f32>,
+    relative_humidity_2m: Option<f32>,
+    weather_code: Option<u8>,
+    is_day: Option<u8>,
+    cloud_cover: Option<f32>,
+    pressure_msl: Option<f32>,
+    wind_speed_10m: Option<f32>,
+    wind_direction_10m: Option<f32>,
+    uv_index: Option<f32>,
+}
+
+/// Hourly variables, one array per variable indexed like `time`
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Hourly {
+    time: Vec<i64>,
+    temperature_2m: Vec<Option<f32>>,
+    apparent_temperature: Vec<Option<f32>>,
+    relative_humidity_2m: Vec<Option<f32>>,
+    precipitation_probability: Vec<Option<f32>>,
+    precipitation: Vec<Option<f32>>,
+    weather_code: Vec<Option<u8>>,
+    is_day: Vec<Option<u8>>,
+    cloud_cover: Vec<Option<f32>>,
+    pressure_msl: Vec<Option<f32>>,
+    wind_speed_10m: Vec<Option<f32>>,
+    wind_direction_10m: Vec<Option<f32>>,
+    uv_index: Vec<Option<f32>>,
+}
+
+/// Daily variables, one array per variable indexed like `time`
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Daily {
+    time: Vec<i64>,
+    weather_code: Vec<Option<u8>>,
+    temperature_2m_max: Vec<Option<f32>>,
+    temperature_2m_min: Vec<Option<f32>>,
+    precipitation_sum: Vec<Option<f32>>,
+    precipitation_probability_max: Vec<Option<f32>>,
+    wind_speed_10m_max: Vec<Option<f32>>,
+}
+
+/// Value at `index` of an hourly or daily array, which has gaps where a model has no data
+fn at<T: Copy>(values: &[Option<T>], index: usize) -> Option<T> {
+    values.get(index).copied().flatten()
+}
+
+impl ForecastApi {
+    fn into_forecast(self) -> Forecast {
+        let hourly = self.hourly();
+        let daily = self.daily();
+
+        // Current conditions have no precipitation forecast, take it from the current hour
+        let current = self.current.map(|current| {
+            let time = DateTime::from_timestamp(current.time, 0).unwrap_or_default();
+            let hour = hourly
+                .iter()
+                .find(|entry| entry.time <= time && time < entry.time + Duration::hours(1));
+
+            Conditions {
+                time,
+                temperature: current.temperature_2m,
+                apparent_temperature: current.apparent_temperature,
+                icon: weather_code_to_icon(
+                    current.weather_code.unwrap_or_default(),
+                    current.is_day != Some(0),
+                )
+                .to_string(),
+                uv_index: current.uv_index,
+                pressure: current.pressure_msl,
+                humidity: current.relative_humidity_2m,
+                cloud_cover: current.cloud_cover,
+                wind_speed: current.wind_speed_10m,
+                wind_direction: current.wind_direction_10m,
+                precipitation: hour.and_then(|hour| hour.precipitation),
+                precipitation_probability: hour.and_then(|hour| hour.precipitation_probability),
+                precipitation_min: None,
+                precipitation_max: None,
+            }
+        });
+
+        Forecast {
+            current,
+            hourly,
+            daily,
+            alerts: Vec::new(),
+            attribution: Attribution {
+                name: String::from("Open-Meteo"),
+                url: String::from("https://open-meteo.com/"),
+            },
+            station: None,
+        }
+    }
+
+    fn hourly(&self) -> Vec<Conditions> {
+        let hourly = &self.hourly;
+
+        hourly
+            .time
+            .iter()
+            .enumerate()
+            .filter_map(|(i, time)| {
+                Some(Conditions {
+                    time: DateTime::from_timestamp(*time, 0)?,
+                    temperature: at(&hourly.temperature_2m, i)?,
+                    apparent_temperature: at(&hourly.apparent_temperature, i),
+                    icon: weather_code_to_icon(
+                        at(&hourly.weather_code, i).unwrap_or_default(),
+                        at(&hourly.is_day, i) != Some(0),
+                    )
+                    .to_string(),
+                    uv_index: at(&hourly.uv_index, i),
+                    pressure: at(&hourly.pressure_msl, i),
+                    humidity: at(&hourly.relative_humidity_2m, i),
+                    cloud_cover: at(&hourly.cloud_cover, i),
+                    wind_speed: at(&hourly.wind_speed_10m, i),
+                    wind_direction: at(&hourly.wind_direction_10m, i),
+                    precipitation: at(&hourly.precipitation, i),
+                    precipitation_probability: at(&hourly.precipitation_probability, i),
+                    precipitation_min: None,
+                    precipitation_max: None,
+                })
+            })
+            .collect()
+    }
+
+    fn daily(&self) -> Vec<DailyForecast> {
+        let daily = &self.daily;
+
+        daily
+            .time
+            .iter()
+            .enumerate()
+            .filter_map(|(i, time)| {
+                // Days start at local midnight, shifting by the offset gives the local date
+                let date =
+                    DateTime::from_timestamp(time + self.utc_offset_seconds, 0)?.date_naive();
+
+                Some(DailyForecast {
+                    date,
+                    min_temperature: at(&daily.temperature_2m_min, i)?,
+                    max_temperature: at(&daily.temperature_2m_max, i)?,
+                    icon: weather_code_to_icon(
+                        at(&daily.weather_code, i).unwrap_or_default(),
+                        true,
+                    )
+                    .to_string(),
+                    precipitation: at(&daily.precipitation_sum, i).unwrap_or_default(),
+                    precipitation_probability: at(&daily.precipitation_probability_max, i),
+                    max_wind_speed: at(&daily.wind_speed_10m_max, i).unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Client for the Open-Meteo forecast API, which needs no API key
+#[derive(Clone)]
+pub struct OpenMeteo {
+    client: reqwest::Client,
+    base_url: String,
+    user_agent: String,
+}
+
+impl OpenMeteo {
+    pub fn new(base_url: &str, user_agent: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            user_agent,
+        }
+    }
+
+    async fn get_forecast(
+        self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<ForecastResponse, FetchError> {
+        // Wind speeds are requested in m/s to match the other providers
+        let url = format!(
+            "{}/v1/forecast?latitude={latitude:.4}&longitude={longitude:.4}\
+            &current={CURRENT_VARIABLES}&hourly={HOURLY_VARIABLES}&daily={DAILY_VARIABLES}\
+            &wind_speed_unit=ms&timeformat=unixtime&timezone=auto&forecast_days={FORECAST_DAYS}",
+            self.base_url
+        );
+
+        let request_builder = self
+            .client
+            .get(url)
+            .header(header::USER_AGENT, &self.user_agent);
+
+        let response = check_status(request_builder.send().await?)?;
+        let data = response.json::<ForecastApi>().await?;
+
+        Ok(ForecastResponse {
+            forecast: Some(data.into_forecast()),
+            expires: Some(Utc::now() + REFRESH_INTERVAL),
+        })
+    }
+}
+
+impl WeatherProvider for OpenMeteo {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::OpenMeteo
+    }
+
+    fn forecast(&self, latitude: f64, longitude: f64) -> ProviderFuture<ForecastResponse> {
+        Box::pin(self.clone().get_forecast(latitude, longitude))
+    }
+}
+
+/// Maps WMO weather interpretation codes to the icon names used for met.no symbols
+fn weather_code_to_icon(code: u8, is_day: bool) -> &'static str {
+    match code {
+        // Clear sky
+        0 if !is_day => "weather-clear-night",
+
+        // Mainly clear, partly cloudy
+        1 | 2 => {
+            if is_day {
+                "weather-few-clouds"
+            } else {
+                "weather-few-clouds-night"
+            }
+        }
+
+        // Overcast
+        3 => "weather-overcast",
+
+        // Fog and depositing rime fog
+        45 | 48 => "weather-fog",
+
+        // Drizzle, rain and their freezing variants
+        51 | 53 | 55 | 56 | 57 | 61 | 63 | 65 | 66 | 67 => "weather-showers",
+
+        // Rain showers
+        80..=82 => "weather-showers-scattered",
+
+        // Snow fall, snow grains and snow showers
+        71 | 73 | 75 | 77 | 85 | 86 => "weather-snow",
+
+        // Thunderstorm, with or without hail
+        95 | 96 | 99 => "weather-storm",
+
+        // Fallback
+        _ => "weather-clear",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::http::mock::{self, MockServer};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/open_meteo.json");
+
+    fn forecast() -> Forecast {
+        serde_json::from_str::<ForecastApi>(FIXTURE)
+            .unwrap()
+            .into_forecast()
+    }
+
+    #[test]
+    fn parses_current_conditions() {
+        let current = forecast().current.unwrap();
+
+        assert_eq!(current.time.timestamp(), 1760782500);
+        assert_eq!(current.temperature, 12.4);
+        assert_eq!(current.apparent_temperature, Some(10.1));
+        assert_eq!(current.icon, "weather-overcast");
+        assert_eq!(current.pressure, Some(1018.2));
+        assert_eq!(current.wind_speed, Some(4.3));
+        // Taken from the hour the current conditions fall into
+        assert_eq!(current.precipitation, Some(0.4));
+        assert_eq!(current.precipitation_probability, Some(35.0));
+    }
+
+    #[test]
+    fn skips_hours_and_days_without_temperatures() {
+        let forecast = forecast();
+
+        let hours: Vec<_> = forecast
+            .hourly
+            .iter()
+            .map(|hour| hour.time.timestamp())
+            .collect();
+        assert_eq!(hours, [1760781600, 1760785200, 1760817600, 1760821200]);
+        assert_eq!(forecast.daily.len(), 2);
+    }
+
+    #[test]
+    fn uses_night_icons_after_dark() {
+        let icons: Vec<_> = forecast()
+            .hourly
+            .into_iter()
+            .map(|hour| hour.icon)
+            .collect();
+
+        assert_eq!(
+            icons,
+            [
+                "weather-showers",
+                "weather-overcast",
+                "weather-clear-night",
+                "weather-few-clouds-night"
+            ]
+        );
+    }
+
+    #[test]
+    fn dates_days_in_the_location_timezone() {
+        let daily = forecast().daily;
+
+        // Local midnight in Berlin is 22:00 UTC on the day before
+        assert_eq!(
+            daily[0].date,
+            NaiveDate::from_ymd_opt(2025, 10, 18).unwrap()
+        );
+        assert_eq!(
+            daily[1].date,
+            NaiveDate::from_ymd_opt(2025, 10, 19).unwrap()
+        );
+        assert_eq!(daily[1].min_temperature, 9.3);
+        assert_eq!(daily[1].max_temperature, 16.8);
+        assert_eq!(daily[1].icon, "weather-storm");
+        assert_eq!(daily[1].precipitation, 5.2);
+        assert_eq!(daily[1].precipitation_probability, Some(80.0));
+    }
+
+    #[test]
+    fn maps_weather_codes_to_icons() {
+        let cases = [
+            (0, true, "weather-clear"),
+            (0, false, "weather-clear-night"),
+            (2, true, "weather-few-clouds"),
+            (2, false, "weather-few-clouds-night"),
+            (3, false, "weather-overcast"),
+            (48, true, "weather-fog"),
+            (57, true, "weather-showers"),
+            (81, true, "weather-showers-scattered"),
+            (77, true, "weather-snow"),
+            (99, false, "weather-storm"),
+            (42, true, "weather-clear"),
+        ];
+
+        for (code, is_day, icon) in cases {
+            assert_eq!(weather_code_to_icon(code, is_day), icon, "code {code}");
+        }
+    }
+
+    #[test]
+    fn requests_the_forecast_from_the_base_url() {
+        let server = MockServer::respond("200 OK", &[], FIXTURE);
+        let provider = OpenMeteo::new(&format!("{}/", server.url), String::from("test-agent"));
+
+        let response = mock::block_on(provider.forecast(52.52, 13.405)).unwrap();
+        let request = server.request();
+
+        assert!(
+            request.starts_with("GET /v1/forecast?latitude=52.5200&longitude=13.4050&"),
+            "{request}"
+        );
+        assert!(request.contains("wind_speed_unit=ms"), "{request}");
+        assert!(
+            request.to_lowercase().contains("user-agent: test-agent"),
+            "{request}"
+        );
+        assert_eq!(response.forecast.unwrap().daily.len(), 2);
+        assert!(response.expires.is_some());
+    }
+
+    #[test]
+    fn reports_unavailable_servers() {
+        let server = MockServer::respond("503 Service Unavailable", &[("Retry-After", "120")], "");
+        let provider = OpenMeteo::new(&server.url, String::new());
+
+        let error = mock::block_on(provider.forecast(52.52, 13.405)).unwrap_err();
+
+        assert_eq!(
+            error,
+            FetchError::Unavailable(Some(std::time::Duration::from_secs(120)))
+        );
+    }
+}
@@ -0,0 +1,483 @@
+use chrono::{DateTime, Duration, Utc};
+use reqwest::header;
+use serde::Deserialize;
+
+use super::{ForecastResponse, ProviderFuture, ProviderKind, WeatherProvider};
+use crate::{
+    error::FetchError,
+    http::check_status,
+    weather::{Alert, AlertSeverity, Attribution, Conditions, DailyForecast, Forecast},
+};
+
+pub const DEFAULT_URL: &str = "https://api.openweathermap.org";
+
+/// OpenWeatherMap updates its data every 10 minutes, which also keeps within the free quota
+const REFRESH_INTERVAL: Duration = Duration::minutes(10);
+
+#[derive(Deserialize)]
+struct OneCallApi {
+    timezone_offset: i64,
+    current: Option<CurrentWeather>,
+    #[serde(default)]
+    hourly: Vec<HourlyWeather>,
+    #[serde(default)]
+    daily: Vec<DailyWeather>,
+    #[serde(default)]
+    alerts: Vec<AlertApi>,
+}
+
+#[derive(Deserialize)]
+struct CurrentWeather {
+    dt: i64,
+    temp: f32,
+    feels_like: Option<f32>,
+    pressure: Option<f32>,
+    humidity: Option<f32>,
+    uvi: Option<f32>,
+    clouds: Option<f32>,
+    wind_speed: Option<f32>,
+    wind_deg: Option<f32>,
+    #[serde(default)]
+    weather: Vec<Condition>,
+}
+
+#[derive(Deserialize)]
+struct HourlyWeather {
+    dt: i64,
+    temp: f32,
+    feels_like: Option<f32>,
+    pressure: Option<f32>,
+    humidity: Option<f32>,
+    uvi: Option<f32>,
+    clouds: Option<f32>,
+    wind_speed: Option<f32>,
+    wind_deg: Option<f32>,
+    #[serde(default)]
+    weather: Vec<Condition>,
+    /// Probability of precipitation between 0 and 1
+    pop: Option<f32>,
+    rain: Option<LastHour>,
+    snow: Option<LastHour>,
+}
+
+/// Precipitation volume, only present when there is any
+#[derive(Deserialize)]
+struct LastHour {
+    #[serde(rename = "1h", default)]
+    one_hour: f32,
+}
+
+#[derive(Deserialize)]
+struct DailyWeather {
+    dt: i64,
+    temp: DailyTemperature,
+    #[serde(default)]
+    weather: Vec<Condition>,
+    pop: Option<f32>,
+    rain: Option<f32>,
+    snow: Option<f32>,
+    wind_speed: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct DailyTemperature {
+    min: f32,
+    max: f32,
+}
+
+#[derive(Deserialize)]
+struct Condition {
+    id: u16,
+    /// Icon code such as "10d", whose suffix tells day from night
+    icon: String,
+}
+
+#[derive(Deserialize)]
+struct AlertApi {
+    event: String,
+    #[serde(default)]
+    description: String,
+    start: i64,
+    end: i64,
+}
+
+/// Icon for the first, primary condition of a list
+fn icon(weather: &[Condition]) -> &'static str {
+    weather.first().map_or("weather-clear", |condition| {
+        condition_to_icon(condition.id, !condition.icon.ends_with('n'))
+    })
+}
+
+impl From<CurrentWeather> for Conditions {
+    fn from(current: CurrentWeather) -> Self {
+        Conditions {
+            time: DateTime::from_timestamp(current.dt, 0).unwrap_or_default(),
+            temperature: current.temp,
+            apparent_temperature: current.feels_like,
+            icon: icon(&current.weather).to_string(),
+            uv_index: current.uvi,
+            pressure: current.pressure,
+            humidity: current.humidity,
+            cloud_cover: current.clouds,
+            wind_speed: current.wind_speed,
+            wind_direction: current.wind_deg,
+            precipitation: None,
+            precipitation_probability: None,
+            precipitation_min: None,
+            precipitation_max: None,
+        }
+    }
+}
+
+impl From<HourlyWeather> for Conditions {
+    fn from(hour: HourlyWeather) -> Self {
+        let precipitation = [hour.rain, hour.snow]
+            .into_iter()
+            .flatten()
+            .map(|volume| volume.one_hour)
+            .sum();
+
+        Conditions {
+            time: DateTime::from_timestamp(hour.dt, 0).unwrap_or_default(),
+            temperature: hour.temp,
+            apparent_temperature: hour.feels_like,
+            icon: icon(&hour.weather).to_string(),
+            uv_index: hour.uvi,
+            pressure: hour.pressure,
+            humidity: hour.humidity,
+            cloud_cover: hour.clouds,
+            wind_speed: hour.wind_speed,
+            wind_direction: hour.wind_deg,
+            precipitation: Some(precipitation),
+            precipitation_probability: hour.pop.map(|pop| pop * 100.0),
+            precipitation_min: None,
+            precipitation_max: None,
+        }
+    }
+}
+
+impl From<AlertApi> for Alert {
+    fn from(alert: AlertApi) -> Self {
+        Alert {
+            title: alert.event,
+            description: alert.description,
+            // One Call does not rate the severity of alerts
+            severity: AlertSeverity::Unknown,
+            start: DateTime::from_timestamp(alert.start, 0),
+            end: DateTime::from_timestamp(alert.end, 0),
+        }
+    }
+}
+
+impl OneCallApi {
+    fn into_forecast(self) -> Forecast {
+        let timezone_offset = self.timezone_offset;
+        let hourly: Vec<Conditions> = self.hourly.into_iter().map(Conditions::from).collect();
+
+        // Current conditions have no precipitation forecast, take it from the current hour
+        let current = self.current.map(|current| {
+            let mut current = Conditions::from(current);
+            if let Some(hour) = hourly.iter().find(|entry| {
+                entry.time <= current.time && current.time < entry.time + Duration::hours(1)
+            }) {
+                current.precipitation = hour.precipitation;
+                current.precipitation_probability = hour.precipitation_probability;
+            }
+            current
+        });
+
+        Forecast {
+            current,
+            hourly,
+            daily: self
+                .daily
+                .into_iter()
+                .filter_map(|day| {
+                    // Days are given at local noon, shifting by the offset gives the local date
+                    let date = DateTime::from_timestamp(day.dt + timezone_offset, 0)?.date_naive();
+
+                    Some(DailyForecast {
+                        date,
+                        min_temperature: day.temp.min,
+                        max_temperature: day.temp.max,
+                        icon: condition_to_icon(
+                            day.weather.first().map_or(800, |condition| condition.id),
+                            true,
+                        )
+                        .to_string(),
+                        precipitation: day.rain.unwrap_or_default() + day.snow.unwrap_or_default(),
+                        precipitation_probability: day.pop.map(|pop| pop * 100.0),
+                        max_wind_speed: day.wind_speed.unwrap_or_default(),
+                    })
+                })
+                .collect(),
+            alerts: self.alerts.into_iter().map(Alert::from).collect(),
+            attribution: Attribution {
+                name: String::from("OpenWeather"),
+                url: String::from("https://openweathermap.org/"),
+            },
+            station: None,
+        }
+    }
+}
+
+/// Client for the OpenWeatherMap One Call API, which requires an API key
+#[derive(Clone)]
+pub struct OpenWeatherMap {
+    client: reqwest::Client,
+    base_url: String,
+    user_agent: String,
+    api_key: String,
+}
+
+impl OpenWeatherMap {
+    pub fn new(base_url: &str, api_key: &str, user_agent: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            user_agent,
+            api_key: api_key.trim().to_string(),
+        }
+    }
+
+    async fn get_forecast(
+        self,
+        latitude: f64,
+        longitude: f64,
+    ) -> Result<ForecastResponse, FetchError> {
+        if self.api_key.is_empty() {
+            return Err(FetchError::MissingApiKey);
+        }
+
+        let url = format!(
+            "{}/data/3.0/onecall?lat={latitude:.4}&lon={longitude:.4}&units=metric\
+            &exclude=minutely&appid={}",
+            self.base_url, self.api_key
+        );
+
+        let request_builder = self
+            .client
+            .get(url)
+            .header(header::USER_AGENT, &self.user_agent);
+
+        let response = check_status(request_builder.send().await?).map_err(|e| match e {
+            FetchError::HttpStatus(401) => FetchError::InvalidApiKey,
+            // The free plan has a daily call limit rather than a rate limit
+            FetchError::RateLimited(_) => FetchError::QuotaExceeded,
+            e => e,
+        })?;
+        let data = response.json::<OneCallApi>().await?;
+
+        Ok(ForecastResponse {
+            forecast: Some(data.into_forecast()),
+            expires: Some(Utc::now() + REFRESH_INTERVAL),
+        })
+    }
+}
+
+impl WeatherProvider for OpenWeatherMap {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::OpenWeatherMap
+    }
+
+    fn forecast(&self, latitude: f64, longitude: f64) -> ProviderFuture<ForecastResponse> {
+        Box::pin(self.clone().get_forecast(latitude, longitude))
+    }
+}
+
+/// Maps OpenWeatherMap condition IDs to the icon names used for met.no symbols
+fn condition_to_icon(id: u16, is_day: bool) -> &'static str {
+    match id {
+        // Thunderstorm
+        200..=299 => "weather-storm",
+
+        // Drizzle
+        300..=399 => "weather-showers",
+
+        // Shower rain
+        520..=531 => "weather-showers-scattered",
+
+        // Rain and freezing rain
+        500..=599 => "weather-showers",
+
+        // Sleet
+        611..=616 => "weather-showers",
+
+        // Snow and shower snow
+        600..=699 => "weather-snow",
+
+        // Tornado
+        781 => "weather-storm",
+
+        // Mist, smoke, haze, dust, fog, sand, ash and squalls
+        700..=799 => "weather-fog",
+
+        // Clear sky
+        800 if !is_day => "weather-clear-night",
+
+        // Few and scattered clouds
+        801 | 802 => {
+            if is_day {
+                "weather-few-clouds"
+            } else {
+                "weather-few-clouds-night"
+            }
+        }
+
+        // Broken and overcast clouds
+        803 | 804 => "weather-overcast",
+
+        // Fallback
+        _ => "weather-clear",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::http::mock::{self, MockServer};
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/open_weather_map.json");
+
+    fn forecast() -> Forecast {
+        serde_json::from_str::<OneCallApi>(FIXTURE)
+            .unwrap()
+            .into_forecast()
+    }
+
+    #[test]
+    fn parses_current_conditions() {
+        let current = forecast().current.unwrap();
+
+        assert_eq!(current.time.timestamp(), 1760830500);
+        assert_eq!(current.temperature, 15.5);
+        assert_eq!(current.apparent_temperature, Some(14.75));
+        assert_eq!(current.icon, "weather-overcast");
+        assert_eq!(current.wind_direction, Some(240.0));
+        // Taken from the hour the current conditions fall into, rain and snow together
+        assert_eq!(current.precipitation, Some(0.75));
+        assert_eq!(current.precipitation_probability, Some(75.0));
+    }
+
+    #[test]
+    fn uses_night_icons_by_their_suffix() {
+        let icons: Vec<_> = forecast()
+            .hourly
+            .into_iter()
+            .map(|hour| hour.icon)
+            .collect();
+
+        assert_eq!(
+            icons,
+            [
+                "weather-showers-scattered",
+                "weather-showers",
+                "weather-clear-night"
+            ]
+        );
+    }
+
+    #[test]
+    fn dates_days_in_the_location_timezone() {
+        let forecast = forecast();
+        let daily = &forecast.daily;
+
+        // Noon in Auckland is 23:00 UTC on the day before
+        assert_eq!(
+            daily[0].date,
+            NaiveDate::from_ymd_opt(2025, 10, 19).unwrap()
+        );
+        assert_eq!(
+            daily[1].date,
+            NaiveDate::from_ymd_opt(2025, 10, 20).unwrap()
+        );
+        assert_eq!(daily[0].min_temperature, 10.5);
+        assert_eq!(daily[0].max_temperature, 17.25);
+        assert_eq!(daily[0].icon, "weather-showers");
+        assert_eq!(daily[0].precipitation, 3.0);
+        assert_eq!(daily[0].precipitation_probability, Some(75.0));
+        assert_eq!(daily[1].precipitation, 0.0);
+
+        assert_eq!(forecast.alerts.len(), 1);
+        assert_eq!(forecast.alerts[0].title, "Strong Wind Watch");
+    }
+
+    #[test]
+    fn maps_condition_ids_to_icons() {
+        let cases = [
+            (211, true, "weather-storm"),
+            (310, true, "weather-showers"),
+            (500, true, "weather-showers"),
+            (511, true, "weather-showers"),
+            (520, true, "weather-showers-scattered"),
+            (531, false, "weather-showers-scattered"),
+            (600, true, "weather-snow"),
+            (611, true, "weather-showers"),
+            (616, true, "weather-showers"),
+            (621, true, "weather-snow"),
+            (741, true, "weather-fog"),
+            (771, true, "weather-fog"),
+            (781, true, "weather-storm"),
+            (800, true, "weather-clear"),
+            (800, false, "weather-clear-night"),
+            (802, true, "weather-few-clouds"),
+            (801, false, "weather-few-clouds-night"),
+            (804, false, "weather-overcast"),
+            (900, true, "weather-clear"),
+        ];
+
+        for (id, is_day, icon) in cases {
+            assert_eq!(condition_to_icon(id, is_day), icon, "id {id}");
+        }
+    }
+
+    #[test]
+    fn requests_the_forecast_with_the_api_key() {
+        let server = MockServer::respond("200 OK", &[], FIXTURE);
+        let provider = OpenWeatherMap::new(
+            &format!("{}/", server.url),
+            " secret ",
+            String::from("test-agent"),
+        );
+
+        let response = mock::block_on(provider.forecast(-36.8485, 174.7633)).unwrap();
+        let request = server.request();
+
+        assert!(
+            request.starts_with(
+                "GET /data/3.0/onecall?lat=-36.8485&lon=174.7633&units=metric\
+                &exclude=minutely&appid=secret "
+            ),
+            "{request}"
+        );
+        assert_eq!(response.forecast.unwrap().daily.len(), 2);
+    }
+
+    #[test]
+    fn requires_an_api_key() {
+        let provider = OpenWeatherMap::new(DEFAULT_URL, " ", String::new());
+
+        let error = mock::block_on(provider.forecast(-36.8485, 174.7633)).unwrap_err();
+
+        assert_eq!(error, FetchError::MissingApiKey);
+    }
+
+    #[test]
+    fn reports_invalid_api_keys_and_exceeded_quotas() {
+        let cases = [
+            ("401 Unauthorized", FetchError::InvalidApiKey),
+            ("429 Too Many Requests", FetchError::QuotaExceeded),
+        ];
+
+        for (status, expected) in cases {
+            let server = MockServer::respond(status, &[("Retry-After", "60")], "{}");
+            let provider = OpenWeatherMap::new(&server.url, "secret", String::new());
+
+            let error = mock::block_on(provider.forecast(-36.8485, 174.7633)).unwrap_err();
+
+            assert_eq!(error, expected, "{status}");
+        }
+    }
+}
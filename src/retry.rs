@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+/// Exponential backoff with jitter for failed fetches.
+///
+/// The jitter spreads out retries of machines that failed at the same time, for
+/// example several applets behind one NAT hitting a rate limit together.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    base: Duration,
+    max: Duration,
+    attempts: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base: Duration::from_secs(30),
+            max: Duration::from_secs(30 * 60),
+            attempts: 0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retrying after another failure. A delay requested by the
+    /// server takes precedence if it is longer than the backoff.
+    pub fn next_delay(&mut self, retry_after: Option<Duration>) -> Duration {
+        let backoff = self
+            .base
+            .saturating_mul(2_u32.saturating_pow(self.attempts))
+            .min(self.max);
+        self.attempts = self.attempts.saturating_add(1);
+
+        // Pick a random delay in the upper half of the backoff
+        let half = backoff / 2;
+        let jittered = half + half.mul_f64(fastrand::f64());
+
+        retry_after.map_or(jittered, |retry_after| retry_after.max(jittered))
+    }
+
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            attempts,
+            ..RetryPolicy::default()
+        }
+    }
+
+    fn assert_between(delay: Duration, min: u64, max: u64) {
+        assert!(
+            Duration::from_secs(min) <= delay && delay <= Duration::from_secs(max),
+            "{delay:?} not within {min}..={max} s"
+        );
+    }
+
+    #[test]
+    fn jitters_within_the_upper_half_of_the_backoff() {
+        for _ in 0..100 {
+            assert_between(policy(0).next_delay(None), 15, 30);
+            assert_between(policy(3).next_delay(None), 120, 240);
+        }
+    }
+
+    #[test]
+    fn doubles_the_backoff_until_the_cap() {
+        let mut policy = policy(0);
+        let bounds = [
+            (15, 30),
+            (30, 60),
+            (60, 120),
+            (120, 240),
+            (240, 480),
+            (480, 960),
+        ];
+
+        for (min, max) in bounds {
+            assert_between(policy.next_delay(None), min, max);
+        }
+        for _ in 0..100 {
+            assert_between(policy.next_delay(None), 900, 1800);
+        }
+    }
+
+    #[test]
+    fn caps_the_backoff_without_overflowing() {
+        assert_between(policy(40).next_delay(None), 900, 1800);
+
+        let mut policy = policy(u32::MAX);
+        assert_between(policy.next_delay(None), 900, 1800);
+        assert_eq!(policy.attempts, u32::MAX);
+    }
+
+    #[test]
+    fn prefers_a_longer_retry_after() {
+        let retry_after = Duration::from_secs(3600);
+        assert_eq!(policy(0).next_delay(Some(retry_after)), retry_after);
+        assert_eq!(policy(40).next_delay(Some(retry_after)), retry_after);
+
+        // A shorter one does not cut the backoff short
+        for _ in 0..100 {
+            assert_between(policy(3).next_delay(Some(Duration::from_secs(5))), 120, 240);
+        }
+    }
+
+    #[test]
+    fn starts_over_after_a_reset() {
+        let mut policy = policy(8);
+        policy.reset();
+
+        assert_between(policy.next_delay(None), 15, 30);
+    }
+}
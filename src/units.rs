@@ -0,0 +1,350 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    pub const ALL: [Self; 3] = [Self::Celsius, Self::Fahrenheit, Self::Kelvin];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Celsius => "°C",
+            Self::Fahrenheit => "°F",
+            Self::Kelvin => "K",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindSpeedUnit {
+    #[default]
+    MetersPerSecond,
+    KilometersPerHour,
+    MilesPerHour,
+    Knots,
+    Beaufort,
+}
+
+impl WindSpeedUnit {
+    pub const ALL: [Self; 5] = [
+        Self::MetersPerSecond,
+        Self::KilometersPerHour,
+        Self::MilesPerHour,
+        Self::Knots,
+        Self::Beaufort,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::MetersPerSecond => "m/s",
+            Self::KilometersPerHour => "km/h",
+            Self::MilesPerHour => "mph",
+            Self::Knots => "kn",
+            Self::Beaufort => "Bft",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PressureUnit {
+    #[default]
+    Hectopascal,
+    InchesOfMercury,
+    MillimetersOfMercury,
+}
+
+impl PressureUnit {
+    pub const ALL: [Self; 3] = [
+        Self::Hectopascal,
+        Self::InchesOfMercury,
+        Self::MillimetersOfMercury,
+    ];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Hectopascal => "hPa",
+            Self::InchesOfMercury => "inHg",
+            Self::MillimetersOfMercury => "mmHg",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrecipitationUnit {
+    #[default]
+    Millimeters,
+    Inches,
+}
+
+impl PrecipitationUnit {
+    pub const ALL: [Self; 2] = [Self::Millimeters, Self::Inches];
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Millimeters => "mm",
+            Self::Inches => "in",
+        }
+    }
+}
+
+/// Temperature in degrees Celsius, as reported by the forecast APIs
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Temperature(pub f32);
+
+impl Temperature {
+    pub fn value(self, unit: TemperatureUnit) -> f32 {
+        match unit {
+            TemperatureUnit::Celsius => self.0,
+            TemperatureUnit::Fahrenheit => self.0 * 9.0 / 5.0 + 32.0,
+            TemperatureUnit::Kelvin => self.0 + 273.15,
+        }
+    }
+
+    /// Formats the temperature rounded to the given number of decimal places
+    pub fn format(self, unit: TemperatureUnit, decimals: u8) -> String {
+        let factor = 10_f32.powi(decimals.into());
+        // Adding zero turns a rounded -0 into 0
+        let value = (self.value(unit) * factor).round() / factor + 0.0;
+
+        format!("{value:.*}{}", decimals.into(), unit.symbol())
+    }
+}
+
+/// Wind speed in metres per second, as reported by the forecast APIs
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct WindSpeed(pub f32);
+
+impl WindSpeed {
+    /// Upper bounds in m/s of Beaufort forces 0 to 11, anything above is force 12
+    const BEAUFORT_LIMITS: [f32; 12] = [
+        0.5, 1.5, 3.3, 5.5, 7.9, 10.7, 13.8, 17.1, 20.7, 24.4, 28.4, 32.6,
+    ];
+
+    pub fn value(self, unit: WindSpeedUnit) -> f32 {
+        match unit {
+            WindSpeedUnit::MetersPerSecond => self.0,
+            WindSpeedUnit::KilometersPerHour => self.0 * 3.6,
+            WindSpeedUnit::MilesPerHour => self.0 * 2.236_936,
+            WindSpeedUnit::Knots => self.0 * 1.943_844,
+            WindSpeedUnit::Beaufort => Self::BEAUFORT_LIMITS
+                .iter()
+                .position(|limit| self.0 < *limit)
+                .unwrap_or(Self::BEAUFORT_LIMITS.len())
+                as f32,
+        }
+    }
+
+    pub fn format(self, unit: WindSpeedUnit) -> String {
+        match unit {
+            WindSpeedUnit::MetersPerSecond => format!("{:.1} {}", self.value(unit), unit.symbol()),
+            WindSpeedUnit::Beaufort => format!("{} {:.0}", unit.symbol(), self.value(unit)),
+            _ => format!("{:.0} {}", self.value(unit), unit.symbol()),
+        }
+    }
+}
+
+/// Air pressure in hectopascals, as reported by the forecast APIs
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Pressure(pub f32);
+
+impl Pressure {
+    pub fn value(self, unit: PressureUnit) -> f32 {
+        match unit {
+            PressureUnit::Hectopascal => self.0,
+            PressureUnit::InchesOfMercury => self.0 * 0.029_53,
+            PressureUnit::MillimetersOfMercury => self.0 * 0.750_062,
+        }
+    }
+
+    pub fn format(self, unit: PressureUnit) -> String {
+        match unit {
+            PressureUnit::InchesOfMercury => format!("{:.2} {}", self.value(unit), unit.symbol()),
+            _ => format!("{:.0} {}", self.value(unit), unit.symbol()),
+        }
+    }
+}
+
+/// Precipitation amount in millimetres, as reported by the forecast APIs
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Precipitation(pub f32);
+
+impl Precipitation {
+    pub fn value(self, unit: PrecipitationUnit) -> f32 {
+        match unit {
+            PrecipitationUnit::Millimeters => self.0,
+            PrecipitationUnit::Inches => self.0 / 25.4,
+        }
+    }
+
+    /// Formats the amount without its unit symbol, for use in translated strings
+    pub fn format_value(self, unit: PrecipitationUnit) -> String {
+        match unit {
+            PrecipitationUnit::Millimeters => format!("{:.1}", self.value(unit)),
+            PrecipitationUnit::Inches => format!("{:.2}", self.value(unit)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnitPreset {
+    Metric,
+    Imperial,
+    Uk,
+}
+
+impl UnitPreset {
+    pub const ALL: [Self; 3] = [Self::Metric, Self::Imperial, Self::Uk];
+
+    /// Picks the preset customary in the given ISO 3166 region
+    pub fn for_region(region: Option<&str>) -> Self {
+        match region {
+            Some("US" | "LR" | "MM") => Self::Imperial,
+            Some("GB") => Self::Uk,
+            _ => Self::Metric,
+        }
+    }
+
+    /// Picks the preset customary in the user's preferred locale
+    pub fn from_locale() -> Self {
+        let languages = i18n_embed::DesktopLanguageRequester::requested_languages();
+        let region = languages
+            .first()
+            .and_then(|language| language.region)
+            .map(|region| region.as_str().to_string());
+
+        Self::for_region(region.as_deref())
+    }
+
+    pub fn units(self) -> Units {
+        match self {
+            Self::Metric => Units {
+                temperature: TemperatureUnit::Celsius,
+                wind_speed: WindSpeedUnit::KilometersPerHour,
+                pressure: PressureUnit::Hectopascal,
+                precipitation: PrecipitationUnit::Millimeters,
+            },
+            Self::Imperial => Units {
+                temperature: TemperatureUnit::Fahrenheit,
+                wind_speed: WindSpeedUnit::MilesPerHour,
+                pressure: PressureUnit::InchesOfMercury,
+                precipitation: PrecipitationUnit::Inches,
+            },
+            Self::Uk => Units {
+                temperature: TemperatureUnit::Celsius,
+                wind_speed: WindSpeedUnit::MilesPerHour,
+                pressure: PressureUnit::Hectopascal,
+                precipitation: PrecipitationUnit::Millimeters,
+            },
+        }
+    }
+}
+
+/// Unit chosen for each displayed quantity
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Units {
+    pub temperature: TemperatureUnit,
+    pub wind_speed: WindSpeedUnit,
+    pub pressure: PressureUnit,
+    pub precipitation: PrecipitationUnit,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_temperatures() {
+        assert_eq!(Temperature(100.0).value(TemperatureUnit::Fahrenheit), 212.0);
+        assert_eq!(Temperature(-40.0).value(TemperatureUnit::Fahrenheit), -40.0);
+        assert_eq!(Temperature(0.0).value(TemperatureUnit::Kelvin), 273.15);
+    }
+
+    #[test]
+    fn formats_temperatures() {
+        assert_eq!(
+            Temperature(21.46).format(TemperatureUnit::Celsius, 0),
+            "21°C"
+        );
+        assert_eq!(
+            Temperature(21.46).format(TemperatureUnit::Celsius, 1),
+            "21.5°C"
+        );
+        assert_eq!(
+            Temperature(20.0).format(TemperatureUnit::Fahrenheit, 0),
+            "68°F"
+        );
+        assert_eq!(Temperature(-0.2).format(TemperatureUnit::Celsius, 0), "0°C");
+    }
+
+    #[test]
+    fn converts_wind_speeds() {
+        assert_eq!(
+            WindSpeed(10.0).format(WindSpeedUnit::MetersPerSecond),
+            "10.0 m/s"
+        );
+        assert_eq!(
+            WindSpeed(10.0).format(WindSpeedUnit::KilometersPerHour),
+            "36 km/h"
+        );
+        assert_eq!(
+            WindSpeed(10.0).format(WindSpeedUnit::MilesPerHour),
+            "22 mph"
+        );
+        assert_eq!(WindSpeed(10.0).format(WindSpeedUnit::Knots), "19 kn");
+    }
+
+    #[test]
+    fn finds_beaufort_forces() {
+        let force = |speed| WindSpeed(speed).value(WindSpeedUnit::Beaufort);
+
+        assert_eq!(force(0.0), 0.0);
+        assert_eq!(force(0.5), 1.0);
+        assert_eq!(force(5.4), 3.0);
+        assert_eq!(force(5.5), 4.0);
+        assert_eq!(force(32.5), 11.0);
+        assert_eq!(force(40.0), 12.0);
+        assert_eq!(WindSpeed(8.0).format(WindSpeedUnit::Beaufort), "Bft 5");
+    }
+
+    #[test]
+    fn converts_pressures() {
+        assert_eq!(
+            Pressure(1013.25).format(PressureUnit::Hectopascal),
+            "1013 hPa"
+        );
+        assert_eq!(
+            Pressure(1013.25).format(PressureUnit::InchesOfMercury),
+            "29.92 inHg"
+        );
+        assert_eq!(
+            Pressure(1013.25).format(PressureUnit::MillimetersOfMercury),
+            "760 mmHg"
+        );
+    }
+
+    #[test]
+    fn converts_precipitation() {
+        assert_eq!(
+            Precipitation(1.25).format_value(PrecipitationUnit::Millimeters),
+            "1.2"
+        );
+        assert_eq!(
+            Precipitation(25.4).format_value(PrecipitationUnit::Inches),
+            "1.00"
+        );
+    }
+
+    #[test]
+    fn picks_presets_by_region() {
+        assert_eq!(UnitPreset::for_region(Some("US")), UnitPreset::Imperial);
+        assert_eq!(UnitPreset::for_region(Some("GB")), UnitPreset::Uk);
+        assert_eq!(UnitPreset::for_region(Some("DE")), UnitPreset::Metric);
+        assert_eq!(UnitPreset::for_region(None), UnitPreset::Metric);
+    }
+}
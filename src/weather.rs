@@ -1,203 +1,227 @@
-use reqwest::header;
-use serde::Deserialize;
-
-use crate::config::APP_ID;
-
-#[derive(Deserialize)]
-pub struct WeatherApi {
-    properties: Properties,
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Probability in percent from which precipitation is considered expected
+const PRECIPITATION_PROBABILITY_THRESHOLD: f32 = 50.0;
+
+/// Weather conditions at a point in time, in metric units: temperatures in °C,
+/// pressure in hPa, wind speed in m/s and precipitation in mm.
+///
+/// Quantities a provider does not report are `None`. Precipitation and the icon
+/// describe the hour following `time`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Conditions {
+    pub time: DateTime<Utc>,
+    pub temperature: f32,
+    pub apparent_temperature: Option<f32>,
+    pub icon: String,
+    pub uv_index: Option<f32>,
+    pub pressure: Option<f32>,
+    pub humidity: Option<f32>,
+    pub cloud_cover: Option<f32>,
+    pub wind_speed: Option<f32>,
+    pub wind_direction: Option<f32>,
+    pub precipitation: Option<f32>,
+    /// Least and most precipitation likely, for providers that report how uncertain
+    /// the amount is
+    pub precipitation_min: Option<f32>,
+    pub precipitation_max: Option<f32>,
+    pub precipitation_probability: Option<f32>,
 }
 
-#[derive(Deserialize)]
-struct Properties {
-    timeseries: Vec<Timeseries>,
+impl Conditions {
+    /// Whether rain is likely in the hour following this entry
+    pub fn expects_precipitation(&self) -> bool {
+        self.precipitation.is_some_and(|amount| amount > 0.0)
+            || self
+                .precipitation_probability
+                .is_some_and(|probability| probability >= PRECIPITATION_PROBABILITY_THRESHOLD)
+    }
 }
 
-#[derive(Deserialize)]
-struct Timeseries {
-    data: Data,
+/// Forecast aggregated over a single local calendar day
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DailyForecast {
+    pub date: NaiveDate,
+    pub min_temperature: f32,
+    pub max_temperature: f32,
+    pub icon: String,
+    pub precipitation: f32,
+    pub precipitation_probability: Option<f32>,
+    pub max_wind_speed: f32,
 }
 
-#[derive(Default, Deserialize)]
-#[serde(default)]
-struct Data {
-    instant: Instant,
-    next_1_hours: Next1Hours,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AlertSeverity {
+    #[default]
+    Unknown,
+    Minor,
+    Moderate,
+    Severe,
+    Extreme,
 }
 
-#[derive(Default, Deserialize)]
+/// Weather warning issued for the location
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(default)]
-struct Instant {
-    details: InstantDetails,
+pub struct Alert {
+    pub title: String,
+    pub description: String,
+    pub severity: AlertSeverity,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
 }
 
-#[derive(Default, Deserialize)]
+/// Credit for the forecast data, which most providers require to be shown
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(default)]
-struct InstantDetails {
-    air_pressure_at_sea_level: f32,
-    air_temperature: f32,
-    cloud_area_fraction: f32,
-    relative_humidity: f32,
-    wind_from_direction: f32,
-    wind_speed: f32,
-    ultraviolet_index_clear_sky: f32,
+pub struct Attribution {
+    pub name: String,
+    pub url: String,
 }
 
-#[derive(Default, Deserialize)]
+/// Forecast for a location, independent of the provider it came from
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(default)]
-struct Next1Hours {
-    summary: Summary,
+pub struct Forecast {
+    /// Observed or nowcast conditions, for providers that report them separately
+    pub current: Option<Conditions>,
+    /// Hourly forecast ordered by time
+    pub hourly: Vec<Conditions>,
+    /// Daily forecast ordered by date
+    pub daily: Vec<DailyForecast>,
+    pub alerts: Vec<Alert>,
+    pub attribution: Attribution,
+    /// Name of the station the current conditions were observed at
+    pub station: Option<String>,
 }
 
-#[derive(Default, Deserialize)]
-#[serde(default)]
-struct Summary {
-    symbol_code: String,
-}
+impl Forecast {
+    /// Conditions to show for `now`: recent observations when the provider has them,
+    /// otherwise the forecast for the current hour
+    pub fn current(&self, now: DateTime<Utc>) -> Option<&Conditions> {
+        self.current
+            .as_ref()
+            .filter(|current| now - current.time < Duration::hours(2))
+            .or_else(|| self.hourly(now, 1).next())
+            .or(self.current.as_ref())
+            .or_else(|| self.hourly.first())
+    }
 
-pub struct WeatherApiResponse {
-    pub temp: i32,
-    pub icon: String,
-    pub uv: f32,
+    /// Hourly entries starting with the current hour, limited to `hours` items
+    pub fn hourly(&self, now: DateTime<Utc>, hours: usize) -> impl Iterator<Item = &Conditions> {
+        self.hourly
+            .iter()
+            .skip_while(move |entry| entry.time + Duration::hours(1) <= now)
+            .take(hours)
+    }
+
+    /// Daily entries starting with `today`, limited to `days` items
+    pub fn daily(&self, today: NaiveDate, days: usize) -> impl Iterator<Item = &DailyForecast> {
+        // A cached forecast may still contain days that are already over
+        self.daily
+            .iter()
+            .skip_while(move |day| day.date < today)
+            .take(days)
+    }
+
+    /// Alerts that have not ended yet
+    pub fn active_alerts(&self, now: DateTime<Utc>) -> impl Iterator<Item = &Alert> {
+        self.alerts
+            .iter()
+            .filter(move |alert| alert.end.is_none_or(|end| end > now))
+    }
 }
 
-impl Default for WeatherApiResponse {
-    fn default() -> Self {
-        Self {
-            temp: 0,
-            icon: String::from("weather-clear"),
-            uv: 0.0,
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 1, hour, minute, 0).unwrap()
+    }
+
+    fn hour(hour: u32, temperature: f32) -> Conditions {
+        Conditions {
+            time: at(hour, 0),
+            temperature,
+            ..Default::default()
         }
     }
-}
 
-impl WeatherApi {
-    pub async fn get_location_forecast(
-        latitude: String,
-        longitude: String,
-    ) -> Result<WeatherApiResponse, reqwest::Error> {
-        let url = format!(
-            "https://api.met.no/weatherapi/locationforecast/2.0/complete?lat={latitude}&lon={longitude}",
-        );
-
-        let request_builder = reqwest::Client::new()
-            .get(url)
-            .header(header::USER_AGENT, APP_ID);
-
-        let response = request_builder.send().await?;
-        let data = response.json::<WeatherApi>().await?;
-
-        let weather = data
-            .properties
-            .timeseries
-            .first()
-            .map(|ts| {
-                let details = &ts.data.instant.details;
-
-                WeatherApiResponse {
-                    temp: details.air_temperature as i32,
-                    icon: Self::symbol_code_to_icon(&ts.data.next_1_hours.summary.symbol_code)
-                        .to_string(),
-                    uv: details.ultraviolet_index_clear_sky,
-                }
-            })
-            .unwrap_or(WeatherApiResponse::default());
-
-        Ok(weather)
+    #[test]
+    fn starts_the_hourly_forecast_with_the_current_hour() {
+        let forecast = Forecast {
+            hourly: (8..14).map(|h| hour(h, h as f32)).collect(),
+            ..Default::default()
+        };
+
+        let temperatures: Vec<_> = forecast
+            .hourly(at(10, 30), 3)
+            .map(|hour| hour.temperature)
+            .collect();
+
+        assert_eq!(temperatures, [10.0, 11.0, 12.0]);
+        assert_eq!(forecast.hourly(at(14, 0), 3).count(), 0);
     }
 
-    /// Maps met.no/MET Norway symbol codes to freedesktop.org weather icon names
-    fn symbol_code_to_icon(symbol_code: &str) -> &'static str {
-        // Parse out the time suffix (_day, _night, _polartwilight)
-        let (base, is_night) = if let Some(base) = symbol_code.strip_suffix("_night") {
-            (base, true)
-        } else if let Some(base) = symbol_code.strip_suffix("_polartwilight") {
-            (base, true)
-        } else if let Some(base) = symbol_code.strip_suffix("_day") {
-            (base, false)
-        } else {
-            (symbol_code, false)
+    #[test]
+    fn prefers_recent_observations_as_current_conditions() {
+        let forecast = Forecast {
+            current: Some(hour(9, 20.0)),
+            hourly: (8..14).map(|h| hour(h, h as f32)).collect(),
+            ..Default::default()
         };
 
-        match base {
-            // Clear sky
-            "clearsky" if is_night => "weather-clear-night",
-
-            // Partly cloudy / fair
-            "fair" | "partlycloudy" => {
-                if is_night {
-                    "weather-few-clouds-night"
-                } else {
-                    "weather-few-clouds"
-                }
-            }
-
-            // Overcast
-            "cloudy" => "weather-overcast",
-
-            // Fog
-            "fog" => "weather-fog",
-
-            // Rain (no thunder)
-            "lightrain" | "rain" | "heavyrain" => "weather-showers",
-
-            // Rain showers (no thunder)
-            "lightrainshowers" | "rainshowers" | "heavyrainshowers" => "weather-showers-scattered",
-
-            // Snow (all variants)
-            "lightsnow" | "snow" | "heavysnow" | "lightsnowshowers" | "snowshowers"
-            | "heavysnowshowers" => "weather-snow",
-
-            // Sleet (rain + snow mix)
-            "lightsleet" | "sleet" | "heavysleet" | "lightsleetshowers" | "sleetshowers"
-            | "heavysleetshowers" => "weather-showers",
-
-            // Thunder variants
-            "lightrainandthunder"
-            | "rainandthunder"
-            | "heavyrainandthunder"
-            | "lightrainshowersandthunder"
-            | "rainshowersandthunder"
-            | "heavyrainshowersandthunder"
-            | "lightsnowandthunder"
-            | "snowandthunder"
-            | "heavysnowandthunder"
-            | "lightssnowshowersandthunder"
-            | "snowshowersandthunder"
-            | "heavysnowshowersandthunder"
-            | "lightsleetandthunder"
-            | "sleetandthunder"
-            | "heavysleetandthunder"
-            | "lightssleetshowersandthunder"
-            | "sleetshowersandthunder"
-            | "heavysleetshowersandthunder" => "weather-storm",
-
-            // Fallback
-            _ => "weather-clear",
-        }
+        assert_eq!(forecast.current(at(10, 30)).unwrap().temperature, 20.0);
+        // Observations turn stale, the forecast for the hour is closer by then
+        assert_eq!(forecast.current(at(11, 30)).unwrap().temperature, 11.0);
+        // Past the forecast, the last known conditions are better than none
+        assert_eq!(forecast.current(at(18, 0)).unwrap().temperature, 20.0);
     }
-}
 
-#[allow(non_snake_case)]
-#[derive(Deserialize, Debug)]
-pub struct IpApi {
-    pub lat: f32,
-    pub lon: f32,
-    pub city: String,
-    pub regionName: String,
-}
+    #[test]
+    fn starts_the_daily_forecast_with_today() {
+        let forecast = Forecast {
+            daily: (1..=5)
+                .map(|day| DailyForecast {
+                    date: NaiveDate::from_ymd_opt(2026, 6, day).unwrap(),
+                    min_temperature: 10.0,
+                    max_temperature: 20.0,
+                    icon: String::from("weather-clear"),
+                    precipitation: 0.0,
+                    precipitation_probability: None,
+                    max_wind_speed: 0.0,
+                })
+                .collect(),
+            ..Default::default()
+        };
 
-impl IpApi {
-    pub async fn get_location_from_ip() -> Result<IpApi, reqwest::Error> {
-        let url = "http://ip-api.com/json?fields=lat,lon,city,regionName";
+        // A cached forecast from two days ago
+        let dates: Vec<_> = forecast
+            .daily(NaiveDate::from_ymd_opt(2026, 6, 3).unwrap(), 7)
+            .map(|day| day.date.to_string())
+            .collect();
 
-        let request_builder = reqwest::Client::new()
-            .get(url)
-            .header(header::USER_AGENT, APP_ID);
+        assert_eq!(dates, ["2026-06-03", "2026-06-04", "2026-06-05"]);
+    }
+
+    #[test]
+    fn expects_precipitation_by_amount_or_probability() {
+        let conditions = |precipitation, precipitation_probability| Conditions {
+            precipitation,
+            precipitation_probability,
+            ..Default::default()
+        };
 
-        let response = request_builder.send().await?;
-        let response = response.json::<IpApi>().await?;
+        assert!(conditions(Some(0.1), None).expects_precipitation());
+        assert!(conditions(Some(0.0), Some(50.0)).expects_precipitation());
+        assert!(conditions(None, Some(80.0)).expects_precipitation());
 
-        Ok(response)
+        assert!(!conditions(Some(0.0), Some(49.0)).expects_precipitation());
+        assert!(!conditions(None, None).expects_precipitation());
     }
 }